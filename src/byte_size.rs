@@ -0,0 +1,30 @@
+/// The units understood by [`parse`] and used by [`format`], in powers of 1024.
+const UNITS: [(&str, u32); 4] = [("TB", 4), ("GB", 3), ("MB", 2), ("KB", 1)];
+
+/// Formats a number of bytes with the largest unit that keeps it above one.
+pub fn format(bytes: usize) -> String {
+    for (unit, power) in UNITS {
+        let size = 1024usize.pow(power);
+        if bytes >= size {
+            return format!("{:.2} {unit}", bytes as f64 / size as f64);
+        }
+    }
+    format!("{bytes} B")
+}
+
+/// Parses a number of bytes with an optional KB, MB, GB or TB suffix, case insensitively.
+pub fn parse(text: &str) -> Option<usize> {
+    let text = text.trim().to_ascii_uppercase();
+    let (number, multiplier) = UNITS
+        .iter()
+        .find_map(|(unit, power)| {
+            text.strip_suffix(unit).map(|number| (number, 1024usize.pow(*power)))
+        })
+        .unwrap_or_else(|| (text.strip_suffix('B').unwrap_or(&text), 1));
+
+    let number: f64 = number.trim().parse().ok()?;
+    if number.is_sign_negative() || !number.is_finite() {
+        return None;
+    }
+    Some((number * multiplier as f64) as usize)
+}
@@ -0,0 +1,148 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::{bail, Context};
+use heed::types::Bytes;
+use heed::{Database, Env};
+
+use crate::database_entries::prefix_entries;
+use crate::stats::DatabaseStats;
+use crate::txn::Txn;
+
+const HELP: &str = "\
+commands:
+    get <key>            prints the value of the key
+    put <key> <value>    inserts or replaces the value of the key
+    delete <key>         deletes the key
+    list [prefix]        prints the entries, tab-separated, starting with the prefix
+    stats                prints statistics about the keys and values
+    use [database]       switches to the named database, or the main one
+    commit               commits the pending writes
+    abort                aborts the pending writes
+keys and values are stfu8-encoded, write spaces as \\x20 in keys";
+
+/// Reads commands from stdin, one per line, and prints their results to stdout.
+///
+/// Writes implicitly start a write transaction that must be committed, the pending
+/// writes are aborted when stdin is closed.
+pub fn run(env: &'static Env, read_only: bool) -> anyhow::Result<()> {
+    let mut txn = Txn::Ro(env.read_txn()?);
+    let mut database: Database<Bytes, Bytes> =
+        env.open_database(txn.read_txn(), None)?.context("missing main database")?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        // The trailing spaces belong to the value of a put, `lines` already removed the `\r\n`.
+        let line = line.trim_start();
+        if line.trim_end().is_empty() {
+            continue;
+        }
+
+        // A failed commit or abort leaves no transaction when a new read one cannot be opened.
+        if txn.is_none() {
+            if let Err(e) = txn.refresh(env) {
+                eprintln!("error: could not open a read transaction: {e}");
+                continue;
+            }
+        }
+
+        let result = execute(env, read_only, &mut txn, &mut database, line, &mut stdout);
+        if let Err(e) = result {
+            eprintln!("error: {e:#}");
+        }
+        stdout.flush()?;
+    }
+
+    if matches!(txn, Txn::Rw(_)) {
+        eprintln!("aborting the uncommitted writes");
+        txn.try_abort(env)?;
+    }
+
+    Ok(())
+}
+
+fn execute(
+    env: &'static Env,
+    read_only: bool,
+    txn: &mut Txn,
+    database: &mut Database<Bytes, Bytes>,
+    line: &str,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let (command, arguments) = line.split_once(' ').unwrap_or((line, ""));
+    let arguments = arguments.trim_start();
+    // Only the value of a put keeps its trailing spaces.
+    let argument = arguments.trim_end();
+
+    match command {
+        "get" => {
+            let key = decode(argument)?;
+            match database.get(txn.read_txn(), &key)? {
+                Some(value) => writeln!(out, "{}", stfu8::encode_u8(value))?,
+                None => bail!("key not found"),
+            }
+        }
+        "put" => {
+            let (key, value) = arguments.split_once(' ').context("usage: put <key> <value>")?;
+            let (key, value) = (decode(key)?, decode(value)?);
+            database.put(write_txn(env, read_only, txn)?, &key, &value)?;
+        }
+        "delete" => {
+            let key = decode(argument)?;
+            if !database.delete(write_txn(env, read_only, txn)?, &key)? {
+                bail!("key not found");
+            }
+        }
+        "list" => {
+            let prefix = decode(argument)?;
+            for result in prefix_entries(*database, txn.read_txn(), &prefix)? {
+                let (key, value) = result?;
+                writeln!(out, "{}\t{}", stfu8::encode_u8(key), stfu8::encode_u8(value))?;
+            }
+        }
+        "stats" => {
+            let stats = DatabaseStats::compute(*database, txn.read_txn())?;
+            writeln!(out, "entries\t{}", stats.entries)?;
+            writeln!(out, "total key bytes\t{}", stats.total_key_bytes)?;
+            writeln!(out, "total value bytes\t{}", stats.total_value_bytes)?;
+            writeln!(out, "key length\t{}..={}", stats.min_key_len, stats.max_key_len)?;
+            writeln!(out, "value length\t{}..={}", stats.min_value_len, stats.max_value_len)?;
+            if stats.truncated {
+                writeln!(out, "truncated\ttrue")?;
+            }
+        }
+        "use" => {
+            let name = Some(argument).filter(|name| !name.is_empty());
+            *database = env
+                .open_database(txn.read_txn(), name)?
+                .with_context(|| format!("database {argument} does not exist"))?;
+        }
+        "commit" => txn.try_commit(env)?,
+        "abort" => txn.try_abort(env)?,
+        "help" => writeln!(out, "{HELP}")?,
+        _ => bail!("unknown command {command:?}, type help to list the commands"),
+    }
+
+    Ok(())
+}
+
+/// Starts a write transaction if there is none and returns it.
+fn write_txn<'t>(
+    env: &'static Env,
+    read_only: bool,
+    txn: &'t mut Txn,
+) -> anyhow::Result<&'t mut heed::RwTxn<'static>> {
+    if read_only {
+        bail!("the environment is opened in read-only mode");
+    }
+    txn.start_write(env)?;
+    match txn {
+        Txn::Rw(wtxn) => Ok(wtxn),
+        Txn::Ro(_) | Txn::None => unreachable!(),
+    }
+}
+
+fn decode(text: &str) -> anyhow::Result<Vec<u8>> {
+    stfu8::decode_u8(text).with_context(|| format!("{text:?} is not a valid stfu8 string"))
+}
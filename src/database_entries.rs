@@ -0,0 +1,2799 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::mem;
+use std::ops::Bound;
+use std::path::PathBuf;
+
+use eframe::egui::{self, Align};
+use egui::Color32;
+use egui_extras::{Column, TableBuilder};
+use egui_tiles::TileId;
+use heed::types::{Bytes, DecodeIgnore, Str};
+use heed::{Database, DatabaseFlags, Env, RoTxn, RwTxn};
+use prost_reflect::MessageDescriptor;
+use regex::Regex;
+
+use crate::display_mode::DisplayMode;
+use crate::errors::{AppError, ReportError};
+use crate::escaped_entry::EscapedEntry;
+use crate::export::{FileFormat, JsonBytes, JsonEntry};
+use crate::format::{decode_structured, detect_format, load_proto_message, DetectedFormat};
+use crate::import::BatchInsert;
+use crate::inspector::Inspector;
+use crate::largest::{Largest, Measure};
+use crate::prefix_groups::PrefixGroups;
+use crate::raw_cursor::RawCursor;
+use crate::sample::Sample;
+use crate::scripting::ScriptLanguage;
+use crate::settings::ProtoSchema;
+use crate::sqlite::SqliteImport;
+use crate::task::BackgroundTask;
+use crate::txn::Txn;
+use crate::{
+    byte_size, import, sqlite, task_status_ui, Pane, TreeBehavior, DEFAULT_WATCH_INTERVAL_SECS,
+    READ_ONLY_HINT,
+};
+
+pub type EntriesIter<'t> = Box<dyn Iterator<Item = heed::Result<(&'t [u8], &'t [u8])>> + 't>;
+
+/// Boxes the iterator, or one that only yields the error when it could not be created.
+fn entries_iter<'t, I>(result: heed::Result<I>) -> EntriesIter<'t>
+where
+    I: Iterator<Item = heed::Result<(&'t [u8], &'t [u8])>> + 't,
+{
+    match result {
+        Ok(iter) => Box::new(iter),
+        Err(e) => Box::new(std::iter::once(Err(e))),
+    }
+}
+
+/// A pane displaying the entries of a database in a table.
+pub struct DatabaseEntries {
+    pub database_name: Option<String>,
+    /// The name given to the tab by the user, displayed instead of the database name.
+    pub alias: Option<String>,
+    pub database: Database<Bytes, Bytes>,
+    /// Whether the database was created with `MDB_DUPSORT` and can store multiple values per key.
+    has_dup_sort: bool,
+    /// The keys of a `MDB_DUPSORT` database whose values are displayed.
+    expanded_keys: HashSet<Vec<u8>>,
+    /// The keys of a dup-sort database and their number of values, read once per read transaction.
+    dup_groups: Option<DupGroups>,
+    entry_to_insert: EscapedEntry,
+    /// Why the key or the value of the edit window could not be decoded.
+    entry_error: Option<String>,
+    /// The value of the entry loaded in the edit window, compared with the edited one.
+    original_value: Option<Vec<u8>>,
+    /// Whether the key field of the edit window must grab the focus.
+    focus_key_field: bool,
+    jump_to_key: String,
+    prefix_filter: String,
+    /// Whether the key filter is a regex matched against the STFU-8 keys instead of a prefix.
+    regex_filter: bool,
+    regex_keys: Option<RegexKeys>,
+    /// The lower and upper bounds of the displayed keys, STFU-8 encoded.
+    range_from: String,
+    range_to: String,
+    /// Whether the entries are displayed in descending key order.
+    reverse: bool,
+    /// The number of filtered entries, counted once per read transaction.
+    row_count: Option<RowCount>,
+    sort_mode: SortMode,
+    /// The filtered entries sorted by value, loaded once per read transaction.
+    sorted_entries: Option<SortedEntries>,
+    /// The number typed in the "go to" field of the table header.
+    go_to_row: String,
+    /// The key of the last row we went to, the iteration starts from it instead of skipping rows.
+    row_anchor: Option<RowAnchor>,
+    /// Whether the table must scroll to the anchor on the next frame.
+    scroll_to_anchor: bool,
+    pub display_mode: DisplayMode,
+    /// Whether the keys and values are displayed with a monospace font.
+    pub use_monospace: bool,
+    /// The schema used by the Protobuf display mode.
+    pub proto_schema: Option<ProtoSchema>,
+    /// The message of the schema, compiled once when the schema is set.
+    proto_message: Option<MessageDescriptor>,
+    schema_window: Option<SchemaWindow>,
+    task: Option<BackgroundTask>,
+    /// The outcome of the last operation.
+    status: Option<String>,
+    import_error: Option<String>,
+    /// The SQLite database whose table is being chosen for an import.
+    sqlite_import: Option<SqliteImport>,
+    batch_insert: Option<BatchInsert>,
+    /// The lines skipped by the batch inserts of this tab.
+    batch_errors: Vec<String>,
+    bulk_delete: Option<BulkDelete>,
+    copy_all: Option<CopyAll>,
+    /// Whether the wipe confirmation window is shown.
+    confirm_wipe: bool,
+    inline_edit: Option<InlineEdit>,
+    /// The key and the formatted structured value displayed in a window.
+    expanded_value: Option<(String, String)>,
+    /// The value displayed in every format in the inspector window.
+    inspected: Option<Inspector>,
+    /// The random entries displayed in a window.
+    sample: Option<Sample>,
+    /// The entries with the largest keys or values displayed in a window.
+    largest: Option<Largest>,
+    /// The number of entries by key prefix displayed in a window.
+    prefix_groups: Option<PrefixGroups>,
+    /// The row whose deletion must be confirmed.
+    row_to_delete: Option<usize>,
+    /// The row highlighted in the table and moved with the arrow keys.
+    selected_row: Option<usize>,
+    /// The keys of the entries checked for the bulk operations.
+    selected_rows: HashSet<Vec<u8>>,
+    /// The last checked row, Shift-clicking another one checks the rows in between.
+    selection_anchor: Option<usize>,
+    /// The number of entries displayed in the tab title and the generation of the
+    /// transaction it was read in, the title is computed from a shared reference.
+    entry_count: Cell<Option<(u64, u64)>>,
+}
+
+/// The filters the cached rows were computed for.
+#[derive(Clone, PartialEq, Eq)]
+struct Filters {
+    key: String,
+    regex: bool,
+    from: String,
+    to: String,
+}
+
+/// The keys matching the regex filter, loaded once per read transaction.
+struct RegexKeys {
+    txn_generation: u64,
+    filters: Filters,
+    reverse: bool,
+    keys: Vec<Vec<u8>>,
+}
+
+/// The number of entries matching the filters, with what it was counted for.
+struct RowCount {
+    txn_generation: u64,
+    filters: Filters,
+    count: usize,
+}
+
+/// A row position along with its key, valid as long as the filters and transaction are the same.
+struct RowAnchor {
+    txn_generation: u64,
+    filters: Filters,
+    index: usize,
+    key: Vec<u8>,
+}
+
+/// The order in which the entries are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    /// The order of the database, no need to load the entries.
+    Key,
+    /// The entries are loaded in memory and sorted by their value bytes.
+    Value,
+    /// The entries are loaded in memory and sorted by the length of their value.
+    ValueLength,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 3] = [SortMode::Key, SortMode::Value, SortMode::ValueLength];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SortMode::Key => "Sort by key",
+            SortMode::Value => "Sort by value",
+            SortMode::ValueLength => "Sort by value length",
+        }
+    }
+}
+
+/// The keys of a dup-sort database with their number of values, with what they were read for.
+struct DupGroups {
+    txn_generation: u64,
+    filters: Filters,
+    reverse: bool,
+    groups: Vec<(Vec<u8>, usize)>,
+}
+
+/// The entries loaded in memory to be sorted by value, with what they were loaded for.
+struct SortedEntries {
+    txn_generation: u64,
+    filters: Filters,
+    sort_mode: SortMode,
+    reverse: bool,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The number of entries above which sorting by value warns about the memory usage.
+const SORT_MEMORY_WARNING: usize = 100_000;
+
+/// The tooltip of the toggles reading a key or data as plain hexadecimal.
+const PLAIN_HEX_HINT: &str = "Reads the field as pairs of hexadecimal digits, e.g. 0xdeadbeef";
+
+/// The state of the Protobuf schema window.
+#[derive(Default)]
+struct SchemaWindow {
+    path: String,
+    message_name: String,
+    error: Option<String>,
+}
+
+/// A value being edited directly in its table row.
+struct InlineEdit {
+    row: usize,
+    key: Vec<u8>,
+    /// The value encoded with the display mode.
+    text: String,
+    /// The text the edit started with, an unchanged value is not written back.
+    original: String,
+    /// Whether the text field must grab the focus, only once when the edit starts.
+    needs_focus: bool,
+}
+
+/// The state of the copy all to another database window.
+#[derive(Default)]
+struct CopyAll {
+    /// The name of the destination database, the unnamed one when empty.
+    destination_name: String,
+    /// Whether the source database is cleared and replaced by the destination once copied.
+    rename: bool,
+    running: Option<CopyProgress>,
+}
+
+/// A copy that is done by chunks, one per frame, in the current write transaction.
+struct CopyProgress {
+    destination: Database<Bytes, Bytes>,
+    /// The last copied key, the copy continues after it and all its duplicates.
+    last_key: Option<Vec<u8>>,
+    count: usize,
+}
+
+/// The number of entries copied in a single frame.
+const COPY_CHUNK_SIZE: usize = 1000;
+
+/// The state of the bulk delete by prefix window.
+#[derive(Default)]
+struct BulkDelete {
+    prefix: String,
+    /// Whether the user asked to delete and must now confirm.
+    confirming: bool,
+}
+
+impl DatabaseEntries {
+    pub fn new(
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        has_dup_sort: bool,
+    ) -> DatabaseEntries {
+        DatabaseEntries {
+            database_name,
+            alias: None,
+            database,
+            has_dup_sort,
+            expanded_keys: HashSet::new(),
+            dup_groups: None,
+            entry_to_insert: EscapedEntry::default(),
+            entry_error: None,
+            original_value: None,
+            focus_key_field: false,
+            jump_to_key: String::new(),
+            prefix_filter: String::new(),
+            regex_filter: false,
+            regex_keys: None,
+            range_from: String::new(),
+            range_to: String::new(),
+            reverse: false,
+            row_count: None,
+            sort_mode: SortMode::Key,
+            sorted_entries: None,
+            go_to_row: String::new(),
+            row_anchor: None,
+            scroll_to_anchor: false,
+            display_mode: DisplayMode::default(),
+            use_monospace: false,
+            proto_schema: None,
+            proto_message: None,
+            schema_window: None,
+            task: None,
+            status: None,
+            import_error: None,
+            sqlite_import: None,
+            batch_insert: None,
+            batch_errors: Vec::new(),
+            bulk_delete: None,
+            copy_all: None,
+            confirm_wipe: false,
+            inline_edit: None,
+            expanded_value: None,
+            inspected: None,
+            sample: None,
+            largest: None,
+            prefix_groups: None,
+            row_to_delete: None,
+            selected_row: None,
+            selected_rows: HashSet::new(),
+            selection_anchor: None,
+            entry_count: Cell::new(None),
+        }
+    }
+
+    /// Compiles the schema and uses it to decode the values in the Protobuf display mode.
+    pub fn set_proto_schema(&mut self, schema: ProtoSchema) -> anyhow::Result<()> {
+        self.proto_message = Some(load_proto_message(&schema.path, &schema.message_name)?);
+        self.proto_schema = Some(schema);
+        Ok(())
+    }
+
+    /// A new tab displaying the same database with the same filters and display mode.
+    pub fn duplicate(&self) -> DatabaseEntries {
+        let mut entries =
+            DatabaseEntries::new(self.database_name.clone(), self.database, self.has_dup_sort);
+        entries.alias = self.alias.clone();
+        entries.prefix_filter = self.prefix_filter.clone();
+        entries.regex_filter = self.regex_filter;
+        entries.range_from = self.range_from.clone();
+        entries.range_to = self.range_to.clone();
+        entries.reverse = self.reverse;
+        entries.sort_mode = self.sort_mode;
+        entries.display_mode = self.display_mode;
+        entries.use_monospace = self.use_monospace;
+        entries.proto_schema = self.proto_schema.clone();
+        entries.proto_message = self.proto_message.clone();
+        entries
+    }
+
+    /// The alias of the tab or the name of the database.
+    pub fn title(&self) -> &str {
+        self.alias.as_deref().or(self.database_name.as_deref()).unwrap_or("{main}")
+    }
+
+    /// The number of entries of the database, read once per read transaction.
+    pub fn entry_count(&self, txn: &Txn, txn_generation: u64) -> Option<u64> {
+        if let (Some((generation, count)), Txn::Ro(_)) = (self.entry_count.get(), txn) {
+            if generation == txn_generation {
+                return Some(count);
+            }
+        }
+        if txn.is_none() {
+            return None;
+        }
+        let count = self.database.len(txn.read_txn()).ok()?;
+        self.entry_count.set(Some((txn_generation, count)));
+        Some(count)
+    }
+
+    pub fn ui(&mut self, behavior: &mut TreeBehavior, ui: &mut egui::Ui, tile_id: TileId) {
+        let DatabaseEntries {
+            database_name,
+            alias: _,
+            database,
+            has_dup_sort,
+            expanded_keys,
+            dup_groups,
+            entry_to_insert,
+            entry_error,
+            original_value,
+            focus_key_field,
+            jump_to_key,
+            prefix_filter,
+            regex_filter,
+            regex_keys,
+            range_from,
+            range_to,
+            reverse,
+            row_count,
+            sort_mode,
+            sorted_entries,
+            go_to_row,
+            row_anchor,
+            scroll_to_anchor,
+            display_mode,
+            use_monospace,
+            proto_schema,
+            proto_message,
+            schema_window,
+            task,
+            status,
+            import_error,
+            sqlite_import,
+            batch_insert,
+            batch_errors,
+            bulk_delete,
+            copy_all,
+            confirm_wipe,
+            inline_edit,
+            expanded_value,
+            inspected,
+            sample,
+            largest,
+            prefix_groups,
+            row_to_delete,
+            selected_row,
+            selected_rows,
+            selection_anchor,
+            entry_count: _,
+        } = self;
+
+        // The prefix is escaped the same way as the entries, we only filter when it decodes.
+        let prefix = stfu8::decode_u8(prefix_filter);
+        let key_regex = if *regex_filter && !prefix_filter.is_empty() {
+            Some(Regex::new(prefix_filter))
+        } else {
+            None
+        };
+        let from = stfu8::decode_u8(range_from);
+        let to = stfu8::decode_u8(range_to);
+        // Whether the first (false) or the last (true) entry must be displayed.
+        let mut go_to_end = None;
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("Go to the first entry").clicked() {
+                go_to_end = Some(false);
+            }
+            if ui.button("⏭").on_hover_text("Go to the last entry").clicked() {
+                go_to_end = Some(true);
+            }
+            ui.add(egui::TextEdit::singleline(jump_to_key).hint_text("jump to key"));
+            let filters = [
+                if *regex_filter {
+                    (&mut *prefix_filter, matches!(key_regex, Some(Err(_))), "filter by key regex")
+                } else {
+                    (&mut *prefix_filter, prefix.is_err(), "filter by key prefix")
+                },
+                (&mut *range_from, from.is_err(), "from"),
+                (&mut *range_to, to.is_err(), "to"),
+            ];
+            for (i, (text, invalid, hint)) in filters.into_iter().enumerate() {
+                let response = ui.add(egui::TextEdit::singleline(text).hint_text(hint));
+                if invalid {
+                    let stroke = egui::Stroke::new(1.0, Color32::RED);
+                    ui.painter().rect_stroke(response.rect, 2.0, stroke);
+                }
+                if i == 0 {
+                    ui.toggle_value(regex_filter, ".*")
+                        .on_hover_text("Match the STFU-8 keys against a regex instead of a prefix");
+                }
+            }
+
+            ui.checkbox(reverse, "Reverse order");
+
+            egui::ComboBox::from_id_source("sort_mode").selected_text(sort_mode.name()).show_ui(
+                ui,
+                |ui| {
+                    for mode in SortMode::ALL {
+                        ui.selectable_value(sort_mode, mode, mode.name());
+                    }
+                },
+            );
+
+            egui::ComboBox::from_id_source("display_mode")
+                .selected_text(display_mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in DisplayMode::ALL {
+                        ui.selectable_value(display_mode, mode, mode.name());
+                    }
+                });
+            ui.checkbox(use_monospace, "Monospace");
+
+            let schema_button = ui.button("Protobuf schema…").on_hover_text(match proto_schema {
+                Some(schema) => format!("{} in {}", schema.message_name, schema.path.display()),
+                None => "No schema configured".to_owned(),
+            });
+            if schema_button.clicked() {
+                *schema_window = Some(match proto_schema {
+                    Some(schema) => SchemaWindow {
+                        path: schema.path.display().to_string(),
+                        message_name: schema.message_name.clone(),
+                        error: None,
+                    },
+                    None => SchemaWindow::default(),
+                });
+            }
+        });
+        let prefix = prefix.ok().filter(|p| !p.is_empty() && !*regex_filter);
+        let key_regex = key_regex.and_then(Result::ok);
+        let from = from.ok().filter(|k| !k.is_empty());
+        let to = to.ok().filter(|k| !k.is_empty());
+        let range = match (&from, &to) {
+            (None, None) => None,
+            (from, to) => Some((
+                from.as_deref().map_or(Bound::Unbounded, Bound::Included),
+                to.as_deref().map_or(Bound::Unbounded, Bound::Included),
+            )),
+        };
+
+        let name = database_name.as_ref().map_or_else(|| "{main}".to_owned(), Clone::clone);
+
+        ui.horizontal(|ui| {
+            if ui.button("Open stats").clicked() {
+                let pane = Pane::DatabaseStats {
+                    database_name: database_name.clone(),
+                    database: *database,
+                    stats: None,
+                };
+                behavior.pane_to_open = Some((tile_id, pane));
+            }
+
+            let sample_button = ui
+                .button(format!("Sample {DEFAULT_SAMPLE_SIZE} entries"))
+                .on_hover_text("Shows random entries without reading the whole database");
+            if sample_button.clicked() {
+                let rtxn = behavior.txn.read_txn();
+                *sample = Some(Sample::new(*database, rtxn, DEFAULT_SAMPLE_SIZE));
+            }
+
+            let largest_button = ui
+                .button(format!("Top {DEFAULT_TOP_K} by size"))
+                .on_hover_text("Shows the entries with the largest values");
+            if largest_button.clicked() {
+                let rtxn = behavior.txn.read_txn();
+                *largest = Some(Largest::new(*database, rtxn, Measure::ValueLength, DEFAULT_TOP_K));
+            }
+
+            let longest_button = ui
+                .button(format!("Top {DEFAULT_TOP_K} longest keys"))
+                .on_hover_text("Shows the entries with the longest keys");
+            if longest_button.clicked() {
+                let rtxn = behavior.txn.read_txn();
+                *largest = Some(Largest::new(*database, rtxn, Measure::KeyLength, DEFAULT_TOP_K));
+            }
+
+            let group_button = ui
+                .button("Group by prefix")
+                .on_hover_text("Counts the entries by the first bytes of their keys");
+            if group_button.clicked() {
+                let rtxn = behavior.txn.read_txn();
+                *prefix_groups = Some(PrefixGroups::new(*database, rtxn, DEFAULT_GROUP_PREFIX_LEN));
+            }
+
+            if ui.button("Split right").on_hover_text("Displays this tab side by side").clicked() {
+                behavior.tile_to_split = Some(tile_id);
+            }
+
+            let cursor_button = ui
+                .button("Raw cursor")
+                .on_hover_text("Moves a cursor one LMDB operation at a time");
+            if cursor_button.clicked() {
+                let cursor = RawCursor::new(database_name.clone(), *database);
+                behavior.pane_to_open = Some((tile_id, Pane::RawCursor(Box::new(cursor))));
+            }
+
+            ui.menu_button("Script console", |ui| {
+                for language in ScriptLanguage::ALL {
+                    if ui.button(language.name()).clicked() {
+                        let pane = Pane::ScriptConsole {
+                            language,
+                            database_name: database_name.clone(),
+                            database: *database,
+                            script: String::new(),
+                            output: String::new(),
+                        };
+                        behavior.pane_to_open = Some((tile_id, pane));
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            ui.menu_button("Diff with…", |ui| {
+                let others =
+                    behavior.open_databases.iter().filter(|(name, _)| name != database_name);
+                for (other_name, other) in others {
+                    if ui.button(other_name.as_deref().unwrap_or("{main}")).clicked() {
+                        let pane = Pane::Diff {
+                            left: *database,
+                            left_name: database_name.clone(),
+                            right: *other,
+                            right_name: other_name.clone(),
+                            diff: None,
+                        };
+                        behavior.pane_to_open = Some((tile_id, pane));
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            let running = task.is_some();
+            ui.add_enabled_ui(!running, |ui| {
+                ui.menu_button("Export", |ui| {
+                    for format in FileFormat::ALL {
+                        if ui.button(format.name()).clicked() {
+                            ui.close_menu();
+                            let path = rfd::FileDialog::new()
+                                .set_file_name(format!("{name}.{}", format.extension()))
+                                .add_filter(format.name(), &[format.extension()])
+                                .save_file();
+                            if let Some(path) = path {
+                                let database = *database;
+                                let env = behavior.env;
+                                *task = Some(BackgroundTask::spawn("Exporting", move |progress| {
+                                    let rtxn = env.read_txn()?;
+                                    let count = format.export(database, &rtxn, &path, progress)?;
+                                    Ok(format!("Exported {count} entries to {}", path.display()))
+                                }));
+                            }
+                        }
+                    }
+
+                    if ui
+                        .button("SQLite")
+                        .on_hover_text("One table with key and value blobs")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        let path = rfd::FileDialog::new()
+                            .set_file_name(format!("{name}.sqlite"))
+                            .add_filter("SQLite", &["sqlite", "db"])
+                            .save_file();
+                        if let Some(path) = path {
+                            let database = *database;
+                            let env = behavior.env;
+                            let table = name.clone();
+                            *task = Some(BackgroundTask::spawn("Exporting", move |progress| {
+                                let rtxn = env.read_txn()?;
+                                let count = sqlite::export_sqlite(
+                                    database, &rtxn, &table, &path, progress,
+                                )?;
+                                Ok(format!("Exported {count} entries to {}", path.display()))
+                            }));
+                        }
+                    }
+                });
+
+                ui.add_enabled_ui(!behavior.read_only, |ui| {
+                    ui.menu_button("Import", |ui| {
+                        for format in FileFormat::ALL {
+                            if ui.button(format.name()).clicked() {
+                                ui.close_menu();
+                                let path = rfd::FileDialog::new()
+                                    .add_filter(format.name(), &[format.extension()])
+                                    .pick_file();
+                                if let Some(path) = path {
+                                    let env = behavior.env;
+                                    behavior
+                                        .txn
+                                        .start_write(env)
+                                        .report(behavior.errors, "Could not start writing");
+                                    if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                        match import::import(format, *database, wtxn, &path) {
+                                            Ok(count) => {
+                                                behavior.txn.try_commit(env).report(
+                                                    behavior.errors,
+                                                    "Could not commit the import",
+                                                );
+                                                behavior.txn_changed = true;
+                                                *status = Some(format!(
+                                                    "Imported {count} entries from {}",
+                                                    path.display()
+                                                ));
+                                            }
+                                            Err(e) => {
+                                                // The entries before the error are written.
+                                                wtxn.mark_dirty();
+                                                *import_error = Some(format!("{e:#}"));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        if ui
+                            .button("SQLite")
+                            .on_hover_text("Choose a table and its columns")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            let path = rfd::FileDialog::new()
+                                .add_filter("SQLite", &["sqlite", "sqlite3", "db"])
+                                .pick_file();
+                            if let Some(path) = path {
+                                *sqlite_import = SqliteImport::open(path)
+                                    .report(behavior.errors, "Could not read the SQLite database");
+                            }
+                        }
+                    })
+                    .response
+                    .on_disabled_hover_text(READ_ONLY_HINT);
+                });
+            });
+
+            let batch_button = ui
+                .add_enabled(
+                    !behavior.read_only && batch_insert.is_none(),
+                    egui::Button::new("Batch insert from file"),
+                )
+                .on_hover_text("Puts the key\tvalue lines of a file, keys and values are STFU-8")
+                .on_disabled_hover_text(READ_ONLY_HINT);
+            if batch_button.clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    match BatchInsert::open(path) {
+                        Ok(batch) => {
+                            behavior
+                                .txn
+                                .start_write(behavior.env)
+                                .report(behavior.errors, "Could not start writing");
+                            *batch_insert = Some(batch);
+                        }
+                        Err(e) => *import_error = Some(e.to_string()),
+                    }
+                }
+            }
+
+            let copy_all_button = ui
+                .add_enabled(!behavior.read_only, egui::Button::new("Copy all to…"))
+                .on_disabled_hover_text(READ_ONLY_HINT);
+            if copy_all_button.clicked() {
+                *copy_all = Some(CopyAll::default());
+            }
+
+            let rename_button = ui
+                .add_enabled(
+                    !behavior.read_only && database_name.is_some(),
+                    egui::Button::new("Rename…"),
+                )
+                .on_disabled_hover_text(if behavior.read_only {
+                    READ_ONLY_HINT
+                } else {
+                    "The unnamed database cannot be renamed"
+                });
+            if rename_button.clicked() {
+                *copy_all = Some(CopyAll { rename: true, ..CopyAll::default() });
+            }
+
+            let bulk_delete_button = ui
+                .add_enabled(!behavior.read_only, egui::Button::new("Bulk delete by prefix"))
+                .on_disabled_hover_text(READ_ONLY_HINT);
+            if bulk_delete_button.clicked() {
+                *bulk_delete = Some(BulkDelete::default());
+            }
+
+            let wipe_button = ui
+                .add_enabled(!behavior.read_only, egui::Button::new("Wipe all entries"))
+                .on_disabled_hover_text(READ_ONLY_HINT);
+            if wipe_button.clicked() {
+                *confirm_wipe = true;
+            }
+
+            task_status_ui(ui, task, status);
+        });
+
+        if let Some(state) = bulk_delete {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new(format!("Bulk delete in {name}"))
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    let decoded = stfu8::decode_u8(&state.prefix);
+                    let response = ui.add_enabled(
+                        !state.confirming,
+                        egui::TextEdit::singleline(&mut state.prefix).hint_text("escaped prefix"),
+                    );
+                    if decoded.is_err() {
+                        ui.painter().rect_stroke(
+                            response.rect,
+                            2.0,
+                            egui::Stroke::new(1.0, Color32::RED),
+                        );
+                    }
+
+                    if !state.confirming {
+                        // LMDB refuses to seek an empty key, wiping the database is another action.
+                        let valid = decoded.as_ref().is_ok_and(|prefix| !prefix.is_empty());
+                        let delete = ui
+                            .add_enabled(valid, egui::Button::new("delete"))
+                            .on_disabled_hover_text(
+                                "Enter a prefix, use Wipe all entries to delete every entry",
+                            );
+                        if delete.clicked() {
+                            state.confirming = true;
+                        }
+                        return;
+                    }
+
+                    ui.colored_label(
+                        Color32::RED,
+                        format!("Delete every entry starting with {:?}?", state.prefix),
+                    );
+                    ui.horizontal(|ui| {
+                        let yes = ui
+                            .add_enabled(!behavior.read_only, egui::Button::new("Yes"))
+                            .on_disabled_hover_text(READ_ONLY_HINT);
+                        if yes.clicked() {
+                            let env = behavior.env;
+                            behavior
+                                .txn
+                                .start_write(env)
+                                .report(behavior.errors, "Could not start writing");
+                            if let (Txn::Rw(ref mut wtxn), Ok(prefix)) =
+                                (&mut *behavior.txn, decoded)
+                            {
+                                let count = delete_prefix(*database, wtxn, &prefix)
+                                    .report(behavior.errors, "Could not delete the entries");
+                                if let Some(count) = count {
+                                    wtxn.mark_dirty();
+                                    behavior.history.record_untracked(count);
+                                    *status = Some(format!("Deleted {count} entries"));
+                                }
+                            }
+                            done = true;
+                        }
+                        if ui.button("No").clicked() {
+                            state.confirming = false;
+                        }
+                    });
+                });
+            if !open || done {
+                *bulk_delete = None;
+            }
+        }
+
+        if let Some(state) = copy_all {
+            let mut open = true;
+            let mut done = false;
+            let title = if state.rename {
+                format!("Rename {name}")
+            } else {
+                format!("Copy all the entries of {name}")
+            };
+            egui::Window::new(title)
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    let Some(progress) = &mut state.running else {
+                        if state.rename {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut state.destination_name)
+                                    .hint_text("new name"),
+                            );
+                            // LMDB cannot rename a database, we copy it and clear the old one.
+                            ui.colored_label(
+                                Color32::YELLOW,
+                                format!("{name} will be emptied, the other tabs displaying it will become stale."),
+                            );
+
+                            let name = state.destination_name.as_str();
+                            let valid = !name.is_empty()
+                                && !behavior.database_names.iter().any(|n| n == name)
+                                && !behavior.read_only;
+                            let rename = ui
+                                .add_enabled(valid, egui::Button::new("rename"))
+                                .on_disabled_hover_text("Choose the name of a new database");
+                            if rename.clicked() {
+                                let env = behavior.env;
+                                behavior.txn.start_write(env).report(behavior.errors, "Could not start writing");
+                                if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                    let destination =
+                                        create_copy_destination(env, wtxn, Some(name), *has_dup_sort)
+                                            .report(behavior.errors, "Could not create the database");
+                                    state.running = destination.map(|destination| CopyProgress {
+                                        destination,
+                                        last_key: None,
+                                        count: 0,
+                                    });
+                                }
+                            }
+                            return;
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut state.destination_name)
+                                    .hint_text("{main} when empty"),
+                            );
+                            egui::ComboBox::from_id_source("copy_all_destination")
+                                .selected_text("opened databases")
+                                .show_ui(ui, |ui| {
+                                    for (name, _) in behavior.open_databases {
+                                        let label = name.as_deref().unwrap_or("{main}");
+                                        if ui.selectable_label(false, label).clicked() {
+                                            state.destination_name =
+                                                name.clone().unwrap_or_default();
+                                        }
+                                    }
+                                });
+                        });
+
+                        let destination_name =
+                            Some(state.destination_name.as_str()).filter(|name| !name.is_empty());
+                        let same = destination_name == database_name.as_deref();
+                        let copy = ui
+                            .add_enabled(!same && !behavior.read_only, egui::Button::new("copy"))
+                            .on_disabled_hover_text(if same {
+                                "The destination is the source database"
+                            } else {
+                                READ_ONLY_HINT
+                            });
+                        if copy.clicked() {
+                            let env = behavior.env;
+                            behavior.txn.start_write(env).report(behavior.errors, "Could not start writing");
+                            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                let destination =
+                                    create_copy_destination(env, wtxn, destination_name, *has_dup_sort)
+                                        .report(behavior.errors, "Could not create the database");
+                                state.running = destination.map(|destination| CopyProgress {
+                                    destination,
+                                    last_key: None,
+                                    count: 0,
+                                });
+                            }
+                        }
+                        return;
+                    };
+
+                    if behavior.read_only {
+                        ui.label("The copy is paused while the writes are locked.");
+                        return;
+                    }
+                    let Txn::Rw(ref mut wtxn) = behavior.txn else {
+                        *status = Some(
+                            "The copy was interrupted by the end of the write transaction"
+                                .to_owned(),
+                        );
+                        done = true;
+                        return;
+                    };
+
+                    let copied = copy_chunk(*database, wtxn, progress);
+                    if progress.count != 0 {
+                        wtxn.mark_dirty();
+                    }
+                    match copied {
+                        Err(e) => {
+                            behavior.errors.push_back(AppError::new("Could not copy the entries", e));
+                            done = true;
+                        }
+                        Ok(true) if state.rename => {
+                            // The old database is only emptied once every entry is in the new one.
+                            let counts = database
+                                .len(wtxn)
+                                .and_then(|len| Ok((len, progress.destination.len(wtxn)?)));
+                            match counts {
+                                Ok((source, destination)) if source == destination => (),
+                                Ok((source, destination)) => {
+                                    *status = Some(format!(
+                                        "The new database has {destination} entries instead of {source}, {name} is kept"
+                                    ));
+                                    done = true;
+                                    return;
+                                }
+                                Err(e) => {
+                                    behavior.errors.push_back(AppError::new("Could not count the entries", e));
+                                    done = true;
+                                    return;
+                                }
+                            }
+                            // heed cannot drop a database, we can only empty the old one.
+                            database.clear(wtxn).report(behavior.errors, "Could not empty the old database");
+                            behavior.history.record_untracked(progress.count * 2);
+                            *status = Some(format!(
+                                "Renamed {name} to {}, the old database is now empty",
+                                state.destination_name
+                            ));
+                            *database_name = Some(mem::take(&mut state.destination_name));
+                            *database = progress.destination;
+                            behavior.txn_changed = true;
+                            done = true;
+                        }
+                        Ok(true) => {
+                            let destination = match state.destination_name.as_str() {
+                                "" => "{main}",
+                                name => name,
+                            };
+                            behavior.history.record_untracked(progress.count);
+                            *status =
+                                Some(format!("Copied {} entries to {destination}", progress.count));
+                            behavior.txn_changed = true;
+                            done = true;
+                        }
+                        Ok(false) => {
+                            ui.spinner();
+                            ui.label(format!("Copied {} entries", progress.count));
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                });
+            if !open || done {
+                *copy_all = None;
+            }
+        }
+
+        if let Some(state) = schema_window {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new(format!("Protobuf schema of {name}"))
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.path).hint_text("schema.proto"),
+                        );
+                        if ui.button("Browse…").clicked() {
+                            let path = rfd::FileDialog::new()
+                                .add_filter("Protobuf", &["proto"])
+                                .pick_file();
+                            if let Some(path) = path {
+                                state.path = path.display().to_string();
+                            }
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.message_name)
+                            .hint_text("package.Message"),
+                    );
+
+                    if let Some(error) = &state.error {
+                        ui.colored_label(Color32::RED, error.as_str());
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("load").clicked() {
+                            let path = PathBuf::from(&state.path);
+                            match load_proto_message(&path, &state.message_name) {
+                                Ok(message) => {
+                                    let message_name = mem::take(&mut state.message_name);
+                                    *proto_schema = Some(ProtoSchema { path, message_name });
+                                    *proto_message = Some(message);
+                                    *display_mode = DisplayMode::Protobuf;
+                                    done = true;
+                                }
+                                Err(e) => state.error = Some(format!("{e:#}")),
+                            }
+                        }
+                        if ui
+                            .add_enabled(proto_schema.is_some(), egui::Button::new("remove"))
+                            .clicked()
+                        {
+                            *proto_schema = None;
+                            *proto_message = None;
+                            done = true;
+                        }
+                    });
+                });
+            if !open || done {
+                *schema_window = None;
+            }
+        }
+
+        if let Some((key, pretty)) = expanded_value {
+            let mut open = true;
+            egui::Window::new(format!("Value of {key}")).open(&mut open).vscroll(true).show(
+                ui.ctx(),
+                |ui| {
+                    // A `&mut &str` makes the text edit read-only.
+                    ui.add(
+                        egui::TextEdit::multiline(&mut pretty.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                },
+            );
+            if !open {
+                *expanded_value = None;
+            }
+        }
+
+        if let Some(inspector) = inspected {
+            let mut open = true;
+            egui::Window::new("Value Inspector")
+                .open(&mut open)
+                .vscroll(true)
+                .show(ui.ctx(), |ui| inspector.ui(ui));
+            if !open {
+                *inspected = None;
+            }
+        }
+
+        if let Some(state) = sample {
+            let mut open = true;
+            egui::Window::new(format!("Random entries of {name}"))
+                .open(&mut open)
+                .vscroll(true)
+                .show(ui.ctx(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut state.count).clamp_range(1..=10_000));
+                        if ui.button("Resample").clicked() {
+                            let rtxn = behavior.txn.read_txn();
+                            *state = Sample::new(*database, rtxn, state.count);
+                        }
+                    });
+                    match &state.entries {
+                        Ok(entries) if entries.is_empty() => {
+                            ui.label("The database is empty");
+                        }
+                        Ok(entries) => {
+                            egui::Grid::new("sample_entries").striped(true).show(ui, |ui| {
+                                for (key, value) in entries {
+                                    ui.monospace(stfu8::encode_u8_pretty(key));
+                                    ui.add(
+                                        egui::Label::new(stfu8::encode_u8_pretty(value))
+                                            .truncate(true),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, e.to_string());
+                        }
+                    }
+                });
+            if !open {
+                *sample = None;
+            }
+        }
+
+        if let Some(state) = largest {
+            let mut open = true;
+            egui::Window::new(format!("{} of {name}", state.measure.title()))
+                .open(&mut open)
+                .vscroll(true)
+                .show(ui.ctx(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Top");
+                        ui.add(egui::DragValue::new(&mut state.k).clamp_range(1..=10_000));
+                        if ui.button("Refresh").clicked() {
+                            let rtxn = behavior.txn.read_txn();
+                            *state = Largest::new(*database, rtxn, state.measure, state.k);
+                        }
+                    });
+                    match &state.entries {
+                        Ok(entries) if entries.is_empty() => {
+                            ui.label("The database is empty");
+                        }
+                        Ok(entries) => {
+                            egui::Grid::new("largest_entries").striped(true).show(ui, |ui| {
+                                for (size, key, value) in entries {
+                                    ui.label(byte_size::format(*size));
+                                    ui.monospace(stfu8::encode_u8_pretty(key));
+                                    ui.add(
+                                        egui::Label::new(stfu8::encode_u8_pretty(value))
+                                            .truncate(true),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, e.to_string());
+                        }
+                    }
+                });
+            if !open {
+                *largest = None;
+            }
+        }
+
+        if let Some(state) = prefix_groups {
+            let mut open = true;
+            egui::Window::new(format!("Key prefixes of {name}")).open(&mut open).show(
+                ui.ctx(),
+                |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Prefix length");
+                        let response = ui.add(
+                            egui::DragValue::new(&mut state.prefix_len)
+                                .clamp_range(1..=32)
+                                .suffix(" bytes"),
+                        );
+                        // Every prefix length would scan the whole database while dragging.
+                        let released = response.drag_released() || response.lost_focus();
+                        if ui.button("Refresh").clicked() || released {
+                            let rtxn = behavior.txn.read_txn();
+                            *state = PrefixGroups::new(*database, rtxn, state.prefix_len);
+                        }
+                    });
+                    match &state.counts {
+                        Ok(counts) if counts.is_empty() => {
+                            ui.label("The database is empty");
+                        }
+                        Ok(counts) => {
+                            ui.label(format!("{} prefixes", counts.len()));
+                            let row_height = ui.text_style_height(&egui::TextStyle::Monospace)
+                                + ui.spacing().item_spacing.y;
+                            egui::ScrollArea::vertical().show_rows(
+                                ui,
+                                row_height,
+                                counts.len(),
+                                |ui, row_range| {
+                                    egui::Grid::new("prefix_groups").striped(true).show(ui, |ui| {
+                                        for (prefix, count) in &counts[row_range] {
+                                            let encoded = stfu8::encode_u8_pretty(prefix);
+                                            let response = ui
+                                                .add(
+                                                    egui::Label::new(
+                                                        egui::RichText::new(&encoded).monospace(),
+                                                    )
+                                                    .sense(egui::Sense::click()),
+                                                )
+                                                .on_hover_text("Filter the entries by this prefix");
+                                            if response.clicked() {
+                                                *regex_filter = false;
+                                                *prefix_filter = encoded;
+                                            }
+                                            ui.label(count.to_string());
+                                            ui.end_row();
+                                        }
+                                    });
+                                },
+                            );
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, e.to_string());
+                        }
+                    }
+                },
+            );
+            if !open {
+                *prefix_groups = None;
+            }
+        }
+
+        if *confirm_wipe {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new(format!("Wipe {name}"))
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    let count = database.len(behavior.txn.read_txn()).unwrap_or_default();
+                    ui.colored_label(
+                        Color32::RED,
+                        format!("Delete all the {count} entries of {name}?"),
+                    );
+                    if !matches!(behavior.txn, Txn::Rw(_)) {
+                        ui.label("A write transaction will be started.");
+                    }
+                    ui.horizontal(|ui| {
+                        let yes = ui
+                            .add_enabled(!behavior.read_only, egui::Button::new("Yes"))
+                            .on_disabled_hover_text(READ_ONLY_HINT);
+                        if yes.clicked() {
+                            let env = behavior.env;
+                            behavior
+                                .txn
+                                .start_write(env)
+                                .report(behavior.errors, "Could not start writing");
+                            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                let cleared = database.len(wtxn).and_then(|count| {
+                                    database.clear(wtxn)?;
+                                    Ok(count)
+                                });
+                                if let Some(count) =
+                                    cleared.report(behavior.errors, "Could not wipe the database")
+                                {
+                                    wtxn.mark_dirty();
+                                    behavior.history.record_untracked(count as usize);
+                                    *status = Some(format!("Deleted {count} entries"));
+                                }
+                            }
+                            done = true;
+                        }
+                        if ui.button("No").clicked() {
+                            done = true;
+                        }
+                    });
+                });
+            if !open || done {
+                *confirm_wipe = false;
+            }
+        }
+
+        if let Some(batch) = batch_insert {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new(format!("Batch insert into {name}"))
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    if behavior.read_only {
+                        ui.label("The batch insert is paused while the writes are locked.");
+                        return;
+                    }
+                    let Txn::Rw(ref mut wtxn) = behavior.txn else {
+                        *status = Some(
+                            "The batch insert was interrupted by the end of the write transaction"
+                                .to_owned(),
+                        );
+                        done = true;
+                        return;
+                    };
+
+                    let inserted = batch.insert_chunk(*database, wtxn, batch_errors);
+                    if batch.inserted != 0 {
+                        wtxn.mark_dirty();
+                    }
+                    match inserted {
+                        Ok(true) => {
+                            behavior
+                                .txn
+                                .try_commit(behavior.env)
+                                .report(behavior.errors, "Could not commit the batch insert");
+                            behavior.txn_changed = true;
+                            *status = Some(format!(
+                                "Inserted {}, skipped {} malformed lines",
+                                batch.inserted, batch.skipped
+                            ));
+                            done = true;
+                        }
+                        Ok(false) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!(
+                                    "Inserted {}, skipped {} malformed lines",
+                                    batch.inserted, batch.skipped
+                                ));
+                            });
+                            ui.ctx().request_repaint();
+                        }
+                        Err(e) => {
+                            *import_error = Some(format!("{}: {e:#}", batch.path.display()));
+                            done = true;
+                        }
+                    }
+                });
+            if !open || done {
+                *batch_insert = None;
+            }
+        }
+
+        if !batch_errors.is_empty() {
+            let mut open = true;
+            egui::Window::new(format!("Batch insert errors ({})", batch_errors.len()))
+                .default_open(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for error in batch_errors.iter() {
+                            ui.colored_label(Color32::RED, error.as_str());
+                        }
+                    });
+                    if ui.button("Clear").clicked() {
+                        batch_errors.clear();
+                    }
+                });
+            if !open {
+                batch_errors.clear();
+            }
+        }
+
+        if let Some(state) = sqlite_import {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new(format!("Import {} into {name}", state.path.display()))
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    if state.tables.is_empty() {
+                        ui.label("This SQLite database has no table.");
+                        return;
+                    }
+                    egui::Grid::new("sqlite_import").num_columns(2).show(ui, |ui| {
+                        ui.label("Table");
+                        let selected = state.tables[state.table].0.clone();
+                        egui::ComboBox::from_id_source("sqlite_table")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for i in 0..state.tables.len() {
+                                    let table = state.tables[i].0.clone();
+                                    if ui.selectable_label(i == state.table, table).clicked() {
+                                        state.select_table(i);
+                                    }
+                                }
+                            });
+                        ui.end_row();
+
+                        let columns = &state.tables[state.table].1;
+                        let targets = [
+                            ("Key column", &mut state.key_column),
+                            ("Value column", &mut state.value_column),
+                        ];
+                        for (label, target) in targets {
+                            ui.label(label);
+                            egui::ComboBox::from_id_source(label)
+                                .selected_text(target.as_str())
+                                .show_ui(ui, |ui| {
+                                    for column in columns {
+                                        ui.selectable_value(target, column.clone(), column);
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+
+                    let import = ui
+                        .add_enabled(!behavior.read_only, egui::Button::new("Import"))
+                        .on_disabled_hover_text(READ_ONLY_HINT);
+                    if import.clicked() {
+                        let env = behavior.env;
+                        behavior
+                            .txn
+                            .start_write(env)
+                            .report(behavior.errors, "Could not start writing");
+                        if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                            match state.import(*database, wtxn) {
+                                Ok(count) => {
+                                    behavior
+                                        .txn
+                                        .try_commit(env)
+                                        .report(behavior.errors, "Could not commit the import");
+                                    behavior.txn_changed = true;
+                                    *status = Some(format!(
+                                        "Imported {count} entries from {}",
+                                        state.path.display()
+                                    ));
+                                }
+                                Err(e) => {
+                                    // The rows before the error are written.
+                                    wtxn.mark_dirty();
+                                    *import_error = Some(format!("{e:#}"));
+                                }
+                            }
+                        }
+                        done = true;
+                    }
+                });
+            if !open || done {
+                *sqlite_import = None;
+            }
+        }
+
+        if let Some(error) = import_error {
+            let mut open = true;
+            egui::Window::new("Import failed")
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ui.colored_label(Color32::RED, error.as_str());
+                    ui.label("The entries read before the error are still in the write transaction, you can abort it.");
+                });
+            if !open {
+                *import_error = None;
+            }
+        }
+        egui::Window::new(format!("Put an entry into {name}")).default_pos([720.0, 480.0]).show(ui.ctx(), |ui| {
+            ui.style_mut().spacing.interact_size.y = 0.0; // hack to make `horizontal_wrapped` work better with text.
+
+            ui.label("We use STFU-8 as a hacky text encoding/decoding protocol for data that might be not quite UTF-8 but is still mostly UTF-8. \
+            It is based on the syntax of the repr created when you write (or print) binary text in python, C or other common programming languages.");
+
+            ui.add_space(8.0);
+
+            ui.label("Basically STFU-8 is the text format you already write when use escape codes in C, python, rust, etc. \
+            It permits binary data in UTF-8 by escaping them with \\, for instance \\n and \\x0F.");
+
+            ui.add_space(8.0);
+
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+                ui.label("More about how we interpret encoding/decoding ");
+                ui.hyperlink_to("on the stfu8 documentation", "https://docs.rs/stfu8");
+                ui.label(".");
+            });
+
+            ui.separator();
+
+            let EscapedEntry { key, data, key_hex, data_hex } = entry_to_insert;
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(key).hint_text("escaped key"));
+                if mem::take(focus_key_field) {
+                    response.request_focus();
+                }
+                ui.checkbox(key_hex, "hex").on_hover_text(PLAIN_HEX_HINT);
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::multiline(data).hint_text("escaped data"));
+                ui.checkbox(data_hex, "hex").on_hover_text(PLAIN_HEX_HINT);
+            });
+
+            // Preview the structured values as they are edited in their raw form.
+            if let Ok(bytes) = entry_to_insert.decoded_data(*display_mode) {
+                if let Some(original) = original_value {
+                    let (changed, added, removed) = byte_diff(original, &bytes);
+                    ui.weak(format!(
+                        "{changed} bytes changed, {added} bytes added, {removed} bytes removed"
+                    ));
+                }
+                match decode_structured(*display_mode, proto_message.as_ref(), &bytes) {
+                    Some((_, Ok(value))) => {
+                        ui.label(egui::RichText::new(value.pretty).monospace());
+                    }
+                    Some((format, Err(e))) => {
+                        ui.colored_label(Color32::RED, format!("invalid {format}: {e}"));
+                    }
+                    None => (),
+                }
+            }
+
+            let insert = ui.add_enabled(!behavior.read_only, egui::Button::new("insert"));
+            if insert.on_disabled_hover_text(READ_ONLY_HINT).clicked() {
+                if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                    let key = entry_to_insert.decoded_key(*display_mode);
+                    let data = entry_to_insert.decoded_data(*display_mode);
+                    match (key, data) {
+                        (Ok(key), Ok(data)) => {
+                            behavior.history.put(wtxn, database_name, *database, &key, &data).report(behavior.errors, "Could not put the entry");
+                            entry_to_insert.clear();
+                            *entry_error = None;
+                            *original_value = None;
+                        }
+                        (Err(e), _) => *entry_error = Some(format!("invalid key: {e}")),
+                        (_, Err(e)) => *entry_error = Some(format!("invalid data: {e}")),
+                    }
+                }
+            }
+
+            let delete = ui.add_enabled(!behavior.read_only, egui::Button::new("delete"));
+            if delete.on_disabled_hover_text(READ_ONLY_HINT).clicked() {
+                if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                    match entry_to_insert.decoded_key(*display_mode) {
+                        Ok(key) => {
+                            behavior.history.delete(wtxn, database_name, *database, &key).report(behavior.errors, "Could not delete the entry");
+                            entry_to_insert.clear();
+                            *entry_error = None;
+                            *original_value = None;
+                        }
+                        Err(e) => *entry_error = Some(format!("invalid key: {e}")),
+                    }
+                }
+            }
+
+            if let Some(error) = entry_error {
+                ui.colored_label(Color32::RED, error.as_str());
+            }
+        });
+
+        if !selected_rows.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} selected", selected_rows.len()));
+
+                let delete = ui
+                    .add_enabled(!behavior.read_only, egui::Button::new("Delete selected"))
+                    .on_disabled_hover_text(READ_ONLY_HINT);
+                if delete.clicked() {
+                    let env = behavior.env;
+                    behavior
+                        .txn
+                        .start_write(env)
+                        .report(behavior.errors, "Could not start writing");
+                    if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                        let mut count = 0;
+                        for key in selected_rows.drain() {
+                            let deleted = behavior
+                                .history
+                                .delete(wtxn, database_name, *database, &key)
+                                .report(behavior.errors, "Could not delete the entry");
+                            count += usize::from(deleted.unwrap_or(false));
+                        }
+                        *status = Some(format!("Deleted {count} entries"));
+                    }
+                }
+
+                ui.add_enabled_ui(task.is_none(), |ui| {
+                    ui.menu_button("Export selected", |ui| {
+                        for format in FileFormat::ALL {
+                            if ui.button(format.name()).clicked() {
+                                ui.close_menu();
+                                let path = rfd::FileDialog::new()
+                                    .set_file_name(format!("{name}.{}", format.extension()))
+                                    .add_filter(format.name(), &[format.extension()])
+                                    .save_file();
+                                if let Some(path) = path {
+                                    let mut keys: Vec<_> = selected_rows.iter().cloned().collect();
+                                    keys.sort_unstable();
+                                    let database = *database;
+                                    let env = behavior.env;
+                                    *task =
+                                        Some(BackgroundTask::spawn("Exporting", move |progress| {
+                                            let rtxn = env.read_txn()?;
+                                            let count = format.export_keys(
+                                                database, &rtxn, &keys, &path, progress,
+                                            )?;
+                                            Ok(format!(
+                                                "Exported {count} entries to {}",
+                                                path.display()
+                                            ))
+                                        }));
+                                }
+                            }
+                        }
+                    });
+                });
+
+                ui.add_enabled_ui(!behavior.read_only, |ui| {
+                    ui.menu_button("Move selected to…", |ui| {
+                        let destinations = behavior
+                            .open_databases
+                            .iter()
+                            .filter(|(name, _)| name != database_name);
+                        for (destination_name, destination) in destinations {
+                            let label = destination_name.as_deref().unwrap_or("{main}");
+                            if ui.button(label).clicked() {
+                                ui.close_menu();
+                                if !matches!(behavior.txn, Txn::Rw(_)) {
+                                    behavior
+                                        .toasts
+                                        .add("Started a write transaction to move the entries");
+                                }
+                                let env = behavior.env;
+                                behavior
+                                    .txn
+                                    .start_write(env)
+                                    .report(behavior.errors, "Could not start writing");
+                                if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                    let mut count = 0;
+                                    for key in selected_rows.drain() {
+                                        let moved = behavior
+                                            .history
+                                            .move_entry(
+                                                wtxn,
+                                                (database_name, *database),
+                                                (destination_name, *destination),
+                                                &key,
+                                            )
+                                            .report(behavior.errors, "Could not move the entry");
+                                        count += usize::from(moved.unwrap_or(false));
+                                    }
+                                    *status = Some(format!("Moved {count} entries to {label}"));
+                                }
+                            }
+                        }
+                    })
+                    .response
+                    .on_disabled_hover_text(READ_ONLY_HINT);
+                });
+
+                if ui.button("Clear selection").clicked() {
+                    selected_rows.clear();
+                }
+            });
+        }
+
+        let rtxn = behavior.txn.read_txn();
+
+        // Copied as the Size header can toggle it while the rows are iterated.
+        let reversed = *reverse;
+        let ranged_entries = || -> EntriesIter {
+            let iter: EntriesIter = match (&range, &prefix, reversed) {
+                (Some(range), _, false) => entries_iter(database.range(rtxn, range)),
+                (Some(range), _, true) => entries_iter(database.rev_range(rtxn, range)),
+                (None, Some(prefix), false) => entries_iter(database.prefix_iter(rtxn, prefix)),
+                (None, Some(prefix), true) => entries_iter(database.rev_prefix_iter(rtxn, prefix)),
+                (None, None, false) => entries_iter(database.iter(rtxn)),
+                (None, None, true) => entries_iter(database.rev_iter(rtxn)),
+            };
+            match (&range, &prefix) {
+                (Some(_), Some(prefix)) => Box::new(
+                    iter.filter(move |r| r.as_ref().map_or(true, |(k, _)| k.starts_with(prefix))),
+                ),
+                _ => iter,
+            }
+        };
+
+        let filters = Filters {
+            key: prefix_filter.clone(),
+            regex: *regex_filter,
+            from: range_from.clone(),
+            to: range_to.clone(),
+        };
+
+        // The keys matching the regex are collected once, their values are fetched when displayed.
+        let regex_matches = match &key_regex {
+            Some(regex) => {
+                let up_to_date = regex_keys.as_ref().is_some_and(|cached| {
+                    cached.txn_generation == behavior.txn_generation
+                        && cached.filters == filters
+                        && cached.reverse == reversed
+                        && matches!(behavior.txn, Txn::Ro(_))
+                });
+                if !up_to_date {
+                    // The keys are cached even when the iteration fails, to report it only once.
+                    let keys = ranged_entries()
+                        .filter(|r| {
+                            r.as_ref().map_or(true, |(key, _)| {
+                                regex.is_match(&stfu8::encode_u8_pretty(key))
+                            })
+                        })
+                        .map(|r| r.map(|(key, _)| key.to_vec()))
+                        .collect::<heed::Result<_>>()
+                        .report(behavior.errors, "Could not filter the entries")
+                        .unwrap_or_default();
+                    *regex_keys = Some(RegexKeys {
+                        txn_generation: behavior.txn_generation,
+                        filters: filters.clone(),
+                        reverse: reversed,
+                        keys,
+                    });
+                }
+                regex_keys.as_ref().map(|cached| &cached.keys)
+            }
+            None => {
+                *regex_keys = None;
+                None
+            }
+        };
+
+        let filtered_entries = || -> EntriesIter {
+            match regex_matches {
+                Some(keys) => Box::new(keys.iter().filter_map(|key| {
+                    database.get(rtxn, key).transpose().map(|r| r.map(|value| (&key[..], value)))
+                })),
+                None => ranged_entries(),
+            }
+        };
+
+        // The values of a dup-sort database are already sorted under their key.
+        let sorted = if *sort_mode != SortMode::Key && !*has_dup_sort {
+            let up_to_date = sorted_entries.as_ref().is_some_and(|sorted| {
+                sorted.txn_generation == behavior.txn_generation
+                    && sorted.filters == filters
+                    && sorted.sort_mode == *sort_mode
+                    && sorted.reverse == *reverse
+                    && matches!(behavior.txn, Txn::Ro(_))
+            });
+            if !up_to_date {
+                let mut entries: Vec<_> = filtered_entries()
+                    .map(|r| r.map(|(k, v)| (k.to_vec(), v.to_vec())))
+                    .collect::<heed::Result<_>>()
+                    .report(behavior.errors, "Could not sort the entries")
+                    .unwrap_or_default();
+                match sort_mode {
+                    SortMode::Key => (),
+                    SortMode::Value => entries.sort_by(|(_, a), (_, b)| a.cmp(b)),
+                    SortMode::ValueLength => entries.sort_by_key(|(_, value)| value.len()),
+                }
+                if *reverse {
+                    entries.reverse();
+                }
+                *sorted_entries = Some(SortedEntries {
+                    txn_generation: behavior.txn_generation,
+                    filters: filters.clone(),
+                    sort_mode: *sort_mode,
+                    reverse: *reverse,
+                    entries,
+                });
+            }
+            sorted_entries.as_ref().map(|sorted| &sorted.entries)
+        } else {
+            *sorted_entries = None;
+            None
+        };
+
+        if let Some(sorted) = sorted.filter(|sorted| sorted.len() > SORT_MEMORY_WARNING) {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!("The {} entries are loaded in memory to be sorted", sorted.len()),
+            );
+        }
+
+        let entries = || -> EntriesIter {
+            match sorted {
+                Some(sorted) => Box::new(sorted.iter().map(|(k, v)| Ok((&k[..], &v[..])))),
+                None => filtered_entries(),
+            }
+        };
+
+        if *has_dup_sort {
+            let up_to_date = dup_groups.as_ref().is_some_and(|groups| {
+                groups.txn_generation == behavior.txn_generation
+                    && groups.filters == filters
+                    && groups.reverse == *reverse
+                    && matches!(behavior.txn, Txn::Ro(_))
+            });
+            if !up_to_date {
+                let groups = group_duplicates(entries())
+                    .report(behavior.errors, "Could not read the entries")
+                    .unwrap_or_default();
+                *dup_groups = Some(DupGroups {
+                    txn_generation: behavior.txn_generation,
+                    filters: filters.clone(),
+                    reverse: *reverse,
+                    groups,
+                });
+            }
+            let groups = dup_groups.as_ref().map_or(&[][..], |groups| &groups.groups);
+            dup_sort_table(
+                ui,
+                *database,
+                rtxn,
+                groups,
+                expanded_keys,
+                *display_mode,
+                *use_monospace,
+            );
+            return;
+        }
+
+        // The jump looks for a key in the key order, there is none when sorting by value.
+        let scroll_to = if !jump_to_key.is_empty() && sorted.is_none() {
+            let mut count = 0;
+            for (i, result) in entries().enumerate() {
+                // The row of the entry that cannot be read displays the error.
+                let Ok((k, _)) = result else { break };
+                count = i;
+                // The rows go backward in reverse order, the jump stops on the same key.
+                let reached = if *reverse {
+                    k <= jump_to_key.as_bytes()
+                } else {
+                    k >= jump_to_key.as_bytes()
+                };
+                if reached {
+                    break;
+                }
+            }
+            Some(count)
+        } else {
+            None
+        };
+
+        let num_rows = if let Some(sorted) = sorted {
+            sorted.len()
+        } else if let Some(keys) = regex_matches {
+            keys.len()
+        } else if range.is_none() && prefix.is_none() {
+            match database.len(rtxn) {
+                Ok(len) => len.try_into().unwrap(),
+                Err(e) => {
+                    ui.colored_label(Color32::RED, format!("Could not count the entries: {e}"));
+                    0
+                }
+            }
+        } else {
+            // A write transaction can change the count at any time, a read one cannot.
+            match row_count {
+                Some(RowCount { txn_generation, filters: counted, count })
+                    if *txn_generation == behavior.txn_generation
+                        && *counted == filters
+                        && matches!(behavior.txn, Txn::Ro(_)) =>
+                {
+                    *count
+                }
+                _ => {
+                    let count = entries().count();
+                    let txn_generation = behavior.txn_generation;
+                    let filters = filters.clone();
+                    *row_count = Some(RowCount { txn_generation, filters, count });
+                    count
+                }
+            }
+        };
+
+        // The keys are only handled for the hovered pane when no text field is being edited.
+        let keyboard = ui.ui_contains_pointer() && ui.memory(|m| m.focus().is_none());
+        let mut scroll_to_selected = false;
+        let mut value_to_put = None;
+
+        // Ctrl+V is not a key press but a paste event containing the clipboard text.
+        let pasted = ui.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            })
+        });
+        if let Some(text) = pasted.filter(|_| keyboard) {
+            match parse_clipboard_entry(&text) {
+                Some(entry) if matches!(behavior.txn, Txn::Rw(_)) && !behavior.read_only => {
+                    value_to_put = Some(entry);
+                    *status = Some("Pasted 1 entry".to_owned());
+                }
+                // Let the user inspect and fix the entry in the edit window.
+                _ => match text.split_once('\t') {
+                    Some((key, data)) => entry_to_insert.prefill(key.to_owned(), data.to_owned()),
+                    None => entry_to_insert.prefill(text, String::new()),
+                },
+            }
+            *original_value = None;
+        }
+
+        if keyboard && num_rows != 0 {
+            let select_all =
+                ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::A));
+            if select_all {
+                // The keys of all the filtered entries are loaded in memory.
+                selected_rows.extend(entries().flatten().map(|(key, _)| key.to_vec()));
+            }
+
+            let (up, down, enter, delete, copy) = ui.input(|i| {
+                (
+                    i.key_pressed(egui::Key::ArrowUp),
+                    i.key_pressed(egui::Key::ArrowDown),
+                    i.key_pressed(egui::Key::Enter),
+                    i.key_pressed(egui::Key::Delete),
+                    // Ctrl+C is not a key press but a copy event.
+                    i.events.iter().any(|e| matches!(e, egui::Event::Copy)),
+                )
+            });
+            if up {
+                *selected_row = Some(selected_row.map_or(0, |row| row.saturating_sub(1)));
+                scroll_to_selected = true;
+            }
+            if down {
+                *selected_row = Some(selected_row.map_or(0, |row| (row + 1).min(num_rows - 1)));
+                scroll_to_selected = true;
+            }
+            if let Some(row) = *selected_row {
+                if enter {
+                    if let Some(Ok((key, data))) = entries().nth(row) {
+                        entry_to_insert
+                            .prefill(display_mode.encode_key(key), display_mode.encode_value(data));
+                        *original_value = Some(data.to_vec());
+                    }
+                }
+                if delete && !behavior.read_only {
+                    *row_to_delete = Some(row);
+                }
+                if copy {
+                    if let Some(Ok((key, data))) = entries().nth(row) {
+                        ui.output_mut(|o| o.copied_text = clipboard_entry(key, data));
+                    }
+                }
+            }
+        }
+
+        if let Some(last) = go_to_end.filter(|_| num_rows != 0) {
+            let index = if last { num_rows - 1 } else { 0 };
+            // The last key of the database is directly found without iterating the rows before.
+            let unfiltered = !reversed
+                && sorted.is_none()
+                && regex_matches.is_none()
+                && range.is_none()
+                && prefix.is_none();
+            let key = if last && unfiltered {
+                let last =
+                    database.last(rtxn).report(behavior.errors, "Could not read the last entry");
+                last.flatten().map(|(key, _)| key.to_vec())
+            } else {
+                entries().nth(index).and_then(Result::ok).map(|(key, _)| key.to_vec())
+            };
+            if let Some(key) = key {
+                *row_anchor = Some(RowAnchor {
+                    txn_generation: behavior.txn_generation,
+                    filters: filters.clone(),
+                    index,
+                    key,
+                });
+                *scroll_to_anchor = true;
+                *selected_row = Some(index);
+            }
+        }
+
+        // The anchor is only valid for the rows it was computed for, and the database
+        // range it starts from follows the key order.
+        let anchor = row_anchor
+            .as_ref()
+            .filter(|anchor| {
+                anchor.txn_generation == behavior.txn_generation && anchor.filters == filters
+            })
+            .map(|anchor| (anchor.index, anchor.key.clone()));
+        let anchor_scroll = anchor.as_ref().map(|&(index, _)| index).filter(|_| *scroll_to_anchor);
+        *scroll_to_anchor = false;
+        let anchored_entries = |row_index: usize| -> Option<(EntriesIter, usize)> {
+            let (index, key) = anchor.as_ref()?;
+            if reversed
+                || sorted.is_some()
+                || regex_matches.is_some()
+                || !matches!(behavior.txn, Txn::Ro(_))
+            {
+                return None;
+            }
+            let skip = row_index.checked_sub(*index)?;
+            let end = range.as_ref().map_or(Bound::Unbounded, |&(_, end)| end);
+            // The rows are iterated from the start instead, they display the error.
+            let iter = database.range(rtxn, &(Bound::Included(&key[..]), end)).ok()?;
+            let iter: EntriesIter = match &prefix {
+                // The anchor key has the prefix, the following ones are contiguous.
+                Some(prefix) => Box::new(
+                    iter.take_while(|r| r.as_ref().map_or(true, |(k, _)| k.starts_with(prefix))),
+                ),
+                None => Box::new(iter),
+            };
+            Some((iter, skip))
+        };
+
+        if let Some(pinned_keys) = behavior.bookmarks.get_mut(database_name) {
+            if !pinned_keys.is_empty() {
+                let (mode, monospace) = (*display_mode, *use_monospace);
+                pinned_entries_ui(ui, *database, rtxn, pinned_keys, mode, monospace);
+            }
+        }
+
+        // The iterator already goes backward in reverse order, row N is always its Nth entry.
+        let mut prev_row_index = None;
+        let mut iter = entries();
+
+        let builder = TableBuilder::new(ui).sense(egui::Sense::click());
+        let builder = match (*selected_row).filter(|_| scroll_to_selected) {
+            Some(row) => builder.scroll_to_row(row, None),
+            None => match scroll_to.or(anchor_scroll) {
+                Some(row) => builder.scroll_to_row(row, Some(Align::TOP)),
+                None => builder,
+            },
+        };
+
+        let key_column = match behavior.settings.column_widths.get(database_name) {
+            Some(&[key_width, _]) => Column::initial(key_width),
+            None => Column::auto_with_initial_suggestion(100.0),
+        };
+        let value_column = match behavior.settings.column_widths.get(database_name) {
+            Some(&[_, value_width]) => Column::initial(value_width),
+            None => Column::remainder(),
+        };
+        let mut column_widths = [0.0; 2];
+        let mut key_to_delete = None;
+        let mut entry_to_move = None;
+
+        builder
+            .column(Column::auto().resizable(false))
+            .column(Column::auto().resizable(false))
+            .column(key_column.at_least(100.0).clip(true).resizable(true))
+            .column(value_column.at_least(50.0).clip(true).resizable(true))
+            .column(Column::auto().at_least(40.0))
+            .header(20.0, |mut header| {
+                header.col(|_ui| ());
+                header.col(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Operations");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(go_to_row)
+                                .hint_text("go to #")
+                                .desired_width(60.0),
+                        );
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            match go_to_row.trim().parse::<usize>() {
+                                Ok(n) if (1..=num_rows).contains(&n) => {
+                                    if let Some(Ok((key, _))) = entries().nth(n - 1) {
+                                        *row_anchor = Some(RowAnchor {
+                                            txn_generation: behavior.txn_generation,
+                                            filters: filters.clone(),
+                                            index: n - 1,
+                                            key: key.to_vec(),
+                                        });
+                                        *scroll_to_anchor = true;
+                                        *selected_row = Some(n - 1);
+                                    }
+                                }
+                                _ => *status = Some(format!("There is no entry #{go_to_row}")),
+                            }
+                        }
+                    });
+                });
+                header.col(|ui| {
+                    column_widths[0] = ui.max_rect().width();
+                    ui.label("Keys");
+                });
+                header.col(|ui| {
+                    column_widths[1] = ui.max_rect().width();
+                    ui.horizontal(|ui| {
+                        ui.label("Values");
+                        if *display_mode == DisplayMode::Auto {
+                            // The legend of the row colors.
+                            for format in DetectedFormat::ALL {
+                                let size = egui::vec2(10.0, 10.0);
+                                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                                ui.painter().rect_filled(rect, 2.0, format.color().to_opaque());
+                                ui.small(format.name());
+                            }
+                        }
+                    });
+                });
+                header.col(|ui| {
+                    let label = match (*sort_mode, *reverse) {
+                        (SortMode::ValueLength, false) => "Size ⏶",
+                        (SortMode::ValueLength, true) => "Size ⏷",
+                        _ => "Size",
+                    };
+                    let response = ui
+                        .add(egui::Label::new(label).sense(egui::Sense::click()))
+                        .on_hover_text("Sort by value length");
+                    if response.clicked() {
+                        if *sort_mode == SortMode::ValueLength {
+                            *reverse = !*reverse;
+                        } else {
+                            *sort_mode = SortMode::ValueLength;
+                            *reverse = false;
+                        }
+                    }
+                });
+            })
+            .body(|body| {
+                body.rows(30.0, num_rows, |mut row| {
+                    let row_index = row.index();
+                    assert!(prev_row_index.is_none_or(|p| p + 1 == row_index));
+                    if prev_row_index.is_none() {
+                        let skip = match anchored_entries(row_index) {
+                            Some((anchored, skip)) => {
+                                iter = anchored;
+                                skip
+                            }
+                            None => row_index,
+                        };
+                        iter.by_ref().take(skip).for_each(drop);
+                    }
+                    prev_row_index = Some(row_index);
+
+                    if let Some(result) = iter.next() {
+                        let (key, data) = match result {
+                            Ok(entry) => entry,
+                            Err(e) => {
+                                row.col(|_| ());
+                                row.col(|ui| {
+                                    let error = format!("Could not read the entry: {e}");
+                                    ui.colored_label(Color32::RED, error);
+                                });
+                                return;
+                            }
+                        };
+                        let encoded_key = display_mode.encode_key(key);
+                        let encoded_data = display_mode.encode_value(data);
+                        row.set_selected(*selected_row == Some(row_index));
+
+                        row.col(|ui| {
+                            let mut checked = selected_rows.contains(key);
+                            if ui.checkbox(&mut checked, "").changed() {
+                                let shift = ui.input(|i| i.modifiers.shift);
+                                match (*selection_anchor).filter(|_| shift) {
+                                    Some(anchor) => {
+                                        let first = anchor.min(row_index);
+                                        let count = anchor.abs_diff(row_index) + 1;
+                                        let keys = entries().skip(first).take(count);
+                                        let keys = keys.flatten().map(|(key, _)| key.to_vec());
+                                        if checked {
+                                            selected_rows.extend(keys);
+                                        } else {
+                                            keys.for_each(|key| {
+                                                selected_rows.remove(&key);
+                                            });
+                                        }
+                                    }
+                                    None if checked => {
+                                        selected_rows.insert(key.to_vec());
+                                    }
+                                    None => {
+                                        selected_rows.remove(key);
+                                    }
+                                }
+                                *selection_anchor = Some(row_index);
+                            }
+                        });
+
+                        if *row_to_delete == Some(row_index) {
+                            row.col(|ui| {
+                                let writing =
+                                    matches!(behavior.txn, Txn::Rw(_)) && !behavior.read_only;
+                                let yes = ui
+                                    .add_enabled(writing, egui::Button::new("Yes"))
+                                    .on_disabled_hover_text("Start writing to delete entries");
+                                if yes.clicked() {
+                                    key_to_delete = Some(key.to_vec());
+                                    *row_to_delete = None;
+                                }
+                                if ui.button("No").clicked() {
+                                    *row_to_delete = None;
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.colored_label(
+                                    Color32::RED,
+                                    format!("Delete key {encoded_key:?}?"),
+                                );
+                            });
+                        } else {
+                            row.col(|ui| {
+                                // TODO Replace me by a ✏️
+                                if ui.button("edit").clicked() {
+                                    entry_to_insert
+                                        .prefill(encoded_key.clone(), encoded_data.clone());
+                                    *original_value = Some(data.to_vec());
+                                }
+                                let clone = ui
+                                    .button("clone")
+                                    .on_hover_text("Insert the same value under another key");
+                                if clone.clicked() {
+                                    entry_to_insert.prefill(String::new(), encoded_data.clone());
+                                    *original_value = None;
+                                    *focus_key_field = true;
+                                }
+                                let delete =
+                                    egui::Button::new(egui::RichText::new("🗑").color(Color32::RED));
+                                let delete = ui
+                                    .add_enabled(!behavior.read_only, delete)
+                                    .on_hover_text("Delete this entry")
+                                    .on_disabled_hover_text(READ_ONLY_HINT);
+                                if delete.clicked() {
+                                    *row_to_delete = Some(row_index);
+                                }
+                                let inspect = ui
+                                    .button("🔍")
+                                    .on_hover_text("Inspect the value in every format");
+                                if inspect.clicked() {
+                                    *inspected = Some(Inspector {
+                                        key: encoded_key.clone(),
+                                        value: data.to_vec(),
+                                    });
+                                }
+                                let pinned_keys =
+                                    behavior.bookmarks.entry(database_name.clone()).or_default();
+                                let pinned = pinned_keys.contains(key);
+                                let pin = ui
+                                    .selectable_label(pinned, "📌")
+                                    .on_hover_text("Keep this entry above the table");
+                                if pin.clicked() && !pinned_keys.remove(key) {
+                                    pinned_keys.insert(key.to_vec());
+                                }
+                                ui.add_enabled_ui(!behavior.read_only, |ui| {
+                                    ui.menu_button("move to…", |ui| {
+                                        let destinations = behavior
+                                            .open_databases
+                                            .iter()
+                                            .filter(|(name, _)| name != database_name);
+                                        for (name, destination) in destinations {
+                                            let label = name.as_deref().unwrap_or("{main}");
+                                            if ui.button(label).clicked() {
+                                                entry_to_move =
+                                                    Some((key.to_vec(), name, *destination));
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    })
+                                    .response
+                                    .on_disabled_hover_text(READ_ONLY_HINT);
+                                });
+                            });
+                            row.col(|ui| {
+                                ui.label(data_text(&encoded_key, *use_monospace));
+                            });
+                        }
+                        row.col(|ui| match inline_edit {
+                            Some(edit) if edit.row == row_index => {
+                                // Shift+Enter inserts a new line, Enter alone validates the edit.
+                                let enter = ui.input_mut(|i| {
+                                    i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)
+                                });
+                                let response = ui.add(
+                                    egui::TextEdit::multiline(&mut edit.text)
+                                        .desired_rows(1)
+                                        .desired_width(f32::INFINITY),
+                                );
+                                if edit.needs_focus {
+                                    response.request_focus();
+                                    edit.needs_focus = false;
+                                }
+
+                                let decoded = display_mode.decode_value(&edit.text);
+                                if decoded.is_err() {
+                                    ui.painter().rect_stroke(
+                                        response.rect,
+                                        2.0,
+                                        egui::Stroke::new(1.0, Color32::RED),
+                                    );
+                                }
+
+                                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                    *inline_edit = None;
+                                } else if enter || response.lost_focus() {
+                                    if edit.text == edit.original {
+                                        *inline_edit = None;
+                                    } else if let Ok(value) = decoded {
+                                        value_to_put = Some((edit.key.clone(), value));
+                                        *inline_edit = None;
+                                    }
+                                }
+                            }
+                            _ => {
+                                if *display_mode == DisplayMode::Auto {
+                                    let color = detect_format(data).color();
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+                                }
+                                let (response, expanded) = value_ui(
+                                    ui,
+                                    data,
+                                    &encoded_data,
+                                    *display_mode,
+                                    proto_message.as_ref(),
+                                    *use_monospace,
+                                    behavior.settings.max_value_display_bytes,
+                                );
+                                if let Some(pretty) = expanded {
+                                    *expanded_value = Some((encoded_key.clone(), pretty));
+                                }
+                                // A lossy text would replace the bytes it cannot represent.
+                                if response.clicked()
+                                    && !behavior.read_only
+                                    && !display_mode.is_lossy()
+                                {
+                                    *inline_edit = Some(InlineEdit {
+                                        row: row_index,
+                                        key: key.to_vec(),
+                                        text: encoded_data.clone(),
+                                        original: encoded_data.clone(),
+                                        needs_focus: true,
+                                    });
+                                }
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(data.len().to_string());
+                        });
+
+                        let response = row.response();
+                        if response.clicked() {
+                            *selected_row = Some(row_index);
+                        }
+                        response.context_menu(|ui| {
+                            if ui.button("Edit").clicked() {
+                                entry_to_insert.prefill(encoded_key.clone(), encoded_data.clone());
+                                *original_value = Some(data.to_vec());
+                                ui.close_menu();
+                            }
+                            if ui.button("Clone").clicked() {
+                                entry_to_insert.prefill(String::new(), encoded_data.clone());
+                                *original_value = None;
+                                *focus_key_field = true;
+                                ui.close_menu();
+                            }
+                            let delete = ui
+                                .add_enabled(!behavior.read_only, egui::Button::new("Delete"))
+                                .on_disabled_hover_text(READ_ONLY_HINT);
+                            if delete.clicked() {
+                                *row_to_delete = Some(row_index);
+                                ui.close_menu();
+                            }
+                            ui.add_enabled_ui(!behavior.read_only, |ui| {
+                                ui.menu_button("Move to…", |ui| {
+                                    let destinations = behavior
+                                        .open_databases
+                                        .iter()
+                                        .filter(|(name, _)| name != database_name);
+                                    for (name, destination) in destinations {
+                                        let label = name.as_deref().unwrap_or("{main}");
+                                        if ui.button(label).clicked() {
+                                            entry_to_move =
+                                                Some((key.to_vec(), name, *destination));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_disabled_hover_text(READ_ONLY_HINT);
+                            });
+                            let watch = ui
+                                .button("Watch")
+                                .on_hover_text("Read the value periodically in a new tab");
+                            if watch.clicked() {
+                                let pane = Pane::KeyWatch {
+                                    database_name: database_name.clone(),
+                                    database: *database,
+                                    key: key.to_vec(),
+                                    history: VecDeque::new(),
+                                    interval_secs: DEFAULT_WATCH_INTERVAL_SECS,
+                                    last_poll: None,
+                                };
+                                behavior.pane_to_open = Some((tile_id, pane));
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if ui.button("Copy key").clicked() {
+                                ui.output_mut(|o| o.copied_text = encoded_key);
+                                ui.close_menu();
+                            }
+                            if ui.button("Copy value").clicked() {
+                                ui.output_mut(|o| o.copied_text = encoded_data);
+                                ui.close_menu();
+                            }
+                            if ui.button("Copy as JSON").clicked() {
+                                let entry = JsonEntry {
+                                    key: JsonBytes::from_bytes(key),
+                                    value: JsonBytes::from_bytes(data),
+                                };
+                                let json = serde_json::to_string(&entry).unwrap();
+                                ui.output_mut(|o| o.copied_text = json);
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                });
+            });
+
+        behavior.settings.column_widths.insert(database_name.clone(), column_widths);
+
+        drop(iter);
+        // The writes may have been locked since the edit started.
+        if let Some((key, value)) = value_to_put.filter(|_| !behavior.read_only) {
+            let env = behavior.env;
+            behavior.txn.start_write(env).report(behavior.errors, "Could not start writing");
+            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                behavior
+                    .history
+                    .put(wtxn, database_name, *database, &key, &value)
+                    .report(behavior.errors, "Could not put the entry");
+            }
+        }
+
+        if let Some((key, destination_name, destination)) =
+            entry_to_move.filter(|_| !behavior.read_only)
+        {
+            if !matches!(behavior.txn, Txn::Rw(_)) {
+                behavior.toasts.add("Started a write transaction to move the entry");
+            }
+            let env = behavior.env;
+            behavior.txn.start_write(env).report(behavior.errors, "Could not start writing");
+            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                // Both operations are in the same transaction, the entry is never lost nor duplicated.
+                behavior
+                    .history
+                    .move_entry(
+                        wtxn,
+                        (database_name, *database),
+                        (destination_name, destination),
+                        &key,
+                    )
+                    .report(behavior.errors, "Could not move the entry");
+            }
+        }
+
+        if let Some(key) = key_to_delete.filter(|_| !behavior.read_only) {
+            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                behavior
+                    .history
+                    .delete(wtxn, database_name, *database, &key)
+                    .report(behavior.errors, "Could not delete the entry");
+            }
+        }
+    }
+}
+
+/// Displays the value with a badge when it is decoded from a structured format.
+///
+/// Only the first `max_bytes` of the text are displayed, the full value is in the tooltip.
+/// Returns the response of the value label and the value to show in a window when the
+/// badge or the expand button is clicked.
+fn value_ui(
+    ui: &mut egui::Ui,
+    data: &[u8],
+    encoded_data: &str,
+    display_mode: DisplayMode,
+    proto_message: Option<&MessageDescriptor>,
+    monospace: bool,
+    max_bytes: usize,
+) -> (egui::Response, Option<String>) {
+    ui.horizontal(|ui| {
+        let mut expanded = None;
+        let mut text = Cow::Borrowed(encoded_data);
+        let mut pretty = None;
+        match decode_structured(display_mode, proto_message, data) {
+            Some((_, Ok(value))) => {
+                let badge =
+                    ui.small_button(&value.summary).on_hover_text("Show the formatted value");
+                if badge.clicked() {
+                    expanded = Some(value.pretty.clone());
+                }
+                if let Some(decoded) = value.text {
+                    text = Cow::Owned(decoded);
+                }
+                pretty = Some(value.pretty);
+            }
+            Some((format, Err(e))) => {
+                ui.colored_label(Color32::RED, format!("invalid {format}")).on_hover_text(e);
+                text = Cow::Owned(stfu8::encode_u8_pretty(data));
+            }
+            None => (),
+        }
+
+        let truncated = truncate_text(&text, max_bytes);
+        let label_text = match truncated {
+            Some(truncated) => {
+                if ui.small_button("expand").on_hover_text("Show the full value").clicked() {
+                    expanded = Some(text.to_string());
+                }
+                let more = text.len() - truncated.len();
+                format!("{truncated}…({more} more bytes)")
+            }
+            None => text.to_string(),
+        };
+
+        // The tooltip can contain the full value, it is only built while hovered.
+        let label = egui::Label::new(data_text(label_text, monospace)).sense(egui::Sense::click());
+        let response = ui.add(label).on_hover_ui(|ui| {
+            if truncated.is_some() {
+                ui.label(text.as_ref());
+                ui.add_space(8.0);
+            }
+            ui.label(format!("Detected format: {}", detect_format(data).name()));
+            if let Some(pretty) = &pretty {
+                ui.add_space(8.0);
+                ui.label(pretty);
+            }
+        });
+        (response, expanded)
+    })
+    .inner
+}
+
+/// Compares the values byte by byte and returns the number of changed, added and removed bytes.
+fn byte_diff(old: &[u8], new: &[u8]) -> (usize, usize, usize) {
+    let changed = old.iter().zip(new).filter(|(a, b)| a != b).count();
+    (changed, new.len().saturating_sub(old.len()), old.len().saturating_sub(new.len()))
+}
+
+/// Returns the first `max_bytes` of the text, cut on a char boundary, if it is longer.
+fn truncate_text(text: &str, max_bytes: usize) -> Option<&str> {
+    if text.len() <= max_bytes {
+        return None;
+    }
+    let end = (0..=max_bytes).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+    Some(&text[..end])
+}
+
+/// The number of random entries displayed by the sample window when it opens.
+const DEFAULT_SAMPLE_SIZE: usize = 20;
+
+/// The number of entries displayed by the largest entries window when it opens.
+const DEFAULT_TOP_K: usize = 20;
+
+/// The number of key bytes the entries are grouped by when the prefix window opens.
+const DEFAULT_GROUP_PREFIX_LEN: usize = 4;
+
+/// The flag of the databases storing multiple sorted values per key.
+const MDB_DUPSORT: u16 = 0x04;
+
+/// The maximum number of values displayed under an expanded key.
+const MAX_EXPANDED_VALUES: usize = 100;
+
+/// Iterates over the entries starting with the prefix, or over all of them when it is empty.
+///
+/// LMDB refuses empty keys which makes `prefix_iter` fail with an empty prefix.
+pub fn prefix_entries<'t>(
+    database: Database<Bytes, Bytes>,
+    rtxn: &'t RoTxn,
+    prefix: &[u8],
+) -> heed::Result<EntriesIter<'t>> {
+    if prefix.is_empty() {
+        Ok(Box::new(database.iter(rtxn)?))
+    } else {
+        Ok(Box::new(database.prefix_iter(rtxn, prefix)?))
+    }
+}
+
+/// Returns whether the database was created with `MDB_DUPSORT`.
+///
+/// heed does not expose the database flags, we read them from the `MDB_db` record
+/// stored under the database name in the unnamed database: a `u32` padding
+/// followed by the `u16` flags, in native endianness.
+pub fn is_dup_sort(env: &Env, rtxn: &RoTxn, database_name: Option<&str>) -> bool {
+    let Some(name) = database_name else { return false };
+    let Ok(Some(main_db)) = env.open_database::<Str, Bytes>(rtxn, None) else { return false };
+    match main_db.get(rtxn, name) {
+        Ok(Some(record)) if record.len() >= 6 => {
+            let flags = u16::from_ne_bytes([record[4], record[5]]);
+            flags & MDB_DUPSORT != 0
+        }
+        _ => false,
+    }
+}
+
+/// Displays the pinned entries on a distinct background, they are read one by one.
+pub fn pinned_entries_ui(
+    ui: &mut egui::Ui,
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    pinned_keys: &mut BTreeSet<Vec<u8>>,
+    display_mode: DisplayMode,
+    monospace: bool,
+) {
+    let mut unpinned = None;
+    egui::Frame::none()
+        .fill(Color32::from_rgba_unmultiplied(255, 200, 0, 24))
+        .inner_margin(4.0)
+        .show(ui, |ui| {
+            egui::Grid::new("pinned_entries").num_columns(3).show(ui, |ui| {
+                for key in pinned_keys.iter() {
+                    if ui.small_button("📌").on_hover_text("Unpin this entry").clicked() {
+                        unpinned = Some(key.clone());
+                    }
+                    ui.label(data_text(display_mode.encode_key(key), monospace));
+                    match database.get(rtxn, key) {
+                        Ok(Some(value)) => {
+                            let text = data_text(display_mode.encode_value(value), monospace);
+                            ui.add(egui::Label::new(text).truncate(true));
+                        }
+                        Ok(None) => {
+                            ui.weak("deleted");
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, e.to_string());
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    if let Some(key) = unpinned {
+        pinned_keys.remove(&key);
+    }
+}
+
+/// The text of a key or a value, monospace makes the escaped bytes easier to align.
+fn data_text(text: impl Into<String>, monospace: bool) -> egui::RichText {
+    let text = egui::RichText::new(text);
+    if monospace {
+        text.monospace()
+    } else {
+        text
+    }
+}
+
+/// Displays the entries grouped by key, the values of a key are listed when it is expanded.
+fn dup_sort_table(
+    ui: &mut egui::Ui,
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    groups: &[(Vec<u8>, usize)],
+    expanded_keys: &mut HashSet<Vec<u8>>,
+    display_mode: DisplayMode,
+    monospace: bool,
+) {
+    let heights: Vec<_> = groups
+        .iter()
+        .map(|(key, count)| match expanded_keys.contains(key) {
+            true => 30.0 + 20.0 * (*count).min(MAX_EXPANDED_VALUES + 1) as f32,
+            false => 30.0,
+        })
+        .collect();
+
+    let mut toggled = None;
+    TableBuilder::new(ui)
+        .column(Column::auto().at_least(100.0).clip(true).resizable(true))
+        .column(Column::remainder().at_least(50.0).clip(true))
+        .header(20.0, |mut header| {
+            header.col(|ui| {
+                ui.label("Keys");
+            });
+            header.col(|ui| {
+                ui.label("Values");
+            });
+        })
+        .body(|body| {
+            body.heterogeneous_rows(heights.into_iter(), |mut row| {
+                let (key, count) = &groups[row.index()];
+                let (key, count) = (key.as_slice(), *count);
+                row.col(|ui| {
+                    ui.label(data_text(display_mode.encode_key(key), monospace));
+                });
+                row.col(|ui| {
+                    let response = egui::CollapsingHeader::new(format!("{count} values"))
+                        .id_source(key)
+                        .open(Some(expanded_keys.contains(key)))
+                        .show(ui, |ui| {
+                            let values = match database.get_duplicates(rtxn, key) {
+                                Ok(values) => values,
+                                Err(e) => {
+                                    ui.colored_label(Color32::RED, e.to_string());
+                                    return;
+                                }
+                            };
+                            for result in values.into_iter().flatten().take(MAX_EXPANDED_VALUES) {
+                                match result {
+                                    Ok((_, value)) => ui.label(data_text(
+                                        display_mode.encode_value(value),
+                                        monospace,
+                                    )),
+                                    Err(e) => ui.colored_label(Color32::RED, e.to_string()),
+                                };
+                            }
+                            if count > MAX_EXPANDED_VALUES {
+                                ui.label(format!("and {} more", count - MAX_EXPANDED_VALUES));
+                            }
+                        });
+                    if response.header_response.clicked() {
+                        toggled = Some(key.to_vec());
+                    }
+                });
+            });
+        });
+
+    if let Some(key) = toggled {
+        if !expanded_keys.remove(&key) {
+            expanded_keys.insert(key);
+        }
+    }
+}
+
+/// Returns the keys of the entries along with their number of values.
+fn group_duplicates(entries: EntriesIter) -> heed::Result<Vec<(Vec<u8>, usize)>> {
+    // The keys are sorted, the values of a key are consecutive.
+    let mut groups: Vec<(Vec<u8>, usize)> = Vec::new();
+    for result in entries {
+        let (key, _) = result?;
+        match groups.last_mut() {
+            Some((last, count)) if last == key => *count += 1,
+            _ => groups.push((key.to_vec(), 1)),
+        }
+    }
+    Ok(groups)
+}
+
+/// Formats the entry as a tab-separated STFU-8 key and value.
+///
+/// We do not use the pretty encoding as it keeps the tabs and new lines unescaped.
+fn clipboard_entry(key: &[u8], data: &[u8]) -> String {
+    format!("{}\t{}", stfu8::encode_u8(key), stfu8::encode_u8(data))
+}
+
+/// Parses an entry formatted by [`clipboard_entry`].
+fn parse_clipboard_entry(text: &str) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (key, data) = text.trim_end_matches(['\r', '\n']).split_once('\t')?;
+    Some((stfu8::decode_u8(key).ok()?, stfu8::decode_u8(data).ok()?))
+}
+
+/// Creates the destination of a copy, with the `MDB_DUPSORT` flag when the source has it.
+fn create_copy_destination(
+    env: &Env,
+    wtxn: &mut RwTxn,
+    name: Option<&str>,
+    dup_sort: bool,
+) -> heed::Result<Database<Bytes, Bytes>> {
+    let mut options = env.database_options().types::<Bytes, Bytes>();
+    if let Some(name) = name {
+        options.name(name);
+    }
+    if dup_sort {
+        options.flags(DatabaseFlags::DUP_SORT);
+    }
+    options.create(wtxn)
+}
+
+/// Copies the next chunk of entries into the destination and returns whether the copy is over.
+fn copy_chunk(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    progress: &mut CopyProgress,
+) -> heed::Result<bool> {
+    let range = match &progress.last_key {
+        Some(key) => (Bound::Excluded(key.as_slice()), Bound::Unbounded),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    };
+
+    // We cannot write while iterating in the same transaction, we collect the chunk first.
+    let mut chunk: Vec<(Vec<u8>, Vec<u8>)> = Vec::with_capacity(COPY_CHUNK_SIZE);
+    let mut finished = true;
+    for result in database.range(wtxn, &range)? {
+        let (key, value) = result?;
+        // The next chunk starts after the last key, skipping its duplicates, so a chunk
+        // always ends with all the values of its last key.
+        if chunk.len() >= COPY_CHUNK_SIZE && chunk.last().is_some_and(|(last, _)| last != key) {
+            finished = false;
+            break;
+        }
+        chunk.push((key.to_vec(), value.to_vec()));
+    }
+
+    for (key, value) in &chunk {
+        progress.destination.put(wtxn, key, value)?;
+    }
+    progress.count += chunk.len();
+    progress.last_key = chunk.pop().map(|(key, _)| key);
+    Ok(finished)
+}
+
+/// Deletes every entry starting with the prefix and returns the number of deleted entries.
+///
+/// The prefix must not be empty, LMDB refuses to seek an empty key.
+fn delete_prefix(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    prefix: &[u8],
+) -> heed::Result<usize> {
+    let mut count = 0;
+    let mut iter = database.remap_data_type::<DecodeIgnore>().prefix_iter_mut(wtxn, prefix)?;
+    while let Some(result) = iter.next() {
+        result?;
+        // safety: we do not keep any reference to the entries we delete.
+        unsafe { iter.del_current()? };
+        count += 1;
+    }
+    Ok(count)
+}
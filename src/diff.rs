@@ -0,0 +1,102 @@
+use std::cmp::Ordering;
+
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+
+/// The number of differences after which we stop comparing the databases.
+pub const MAX_DIFFERENCES: usize = 100_000;
+
+/// How an entry differs between the left and right databases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    OnlyInLeft,
+    OnlyInRight,
+    Differs,
+}
+
+/// A key that is missing from one of the databases or that has different values.
+#[derive(Debug)]
+pub struct DiffEntry {
+    pub kind: DiffKind,
+    pub key: Vec<u8>,
+    pub left_value: Option<Vec<u8>>,
+    pub right_value: Option<Vec<u8>>,
+}
+
+/// The key-by-key difference between two databases.
+#[derive(Debug, Default)]
+pub struct DatabaseDiff {
+    pub entries: Vec<DiffEntry>,
+    pub only_in_left: usize,
+    pub only_in_right: usize,
+    pub differs: usize,
+    /// Whether we stopped comparing after `MAX_DIFFERENCES` differences.
+    pub truncated: bool,
+}
+
+impl DatabaseDiff {
+    /// Iterates over both databases at the same time, like a merge-join, the keys being sorted.
+    pub fn compute(
+        left: Database<Bytes, Bytes>,
+        right: Database<Bytes, Bytes>,
+        rtxn: &RoTxn,
+    ) -> heed::Result<DatabaseDiff> {
+        let mut diff = DatabaseDiff::default();
+        let mut left_iter = left.iter(rtxn)?;
+        let mut right_iter = right.iter(rtxn)?;
+        let mut left_entry = left_iter.next().transpose()?;
+        let mut right_entry = right_iter.next().transpose()?;
+
+        loop {
+            if diff.entries.len() == MAX_DIFFERENCES {
+                diff.truncated = true;
+                break;
+            }
+
+            let ordering = match (left_entry, right_entry) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((lkey, _)), Some((rkey, _))) => lkey.cmp(rkey),
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    let (key, value) = left_entry.unwrap();
+                    diff.push(DiffKind::OnlyInLeft, key, Some(value), None);
+                    left_entry = left_iter.next().transpose()?;
+                }
+                Ordering::Greater => {
+                    let (key, value) = right_entry.unwrap();
+                    diff.push(DiffKind::OnlyInRight, key, None, Some(value));
+                    right_entry = right_iter.next().transpose()?;
+                }
+                Ordering::Equal => {
+                    let (key, lvalue) = left_entry.unwrap();
+                    let (_, rvalue) = right_entry.unwrap();
+                    if lvalue != rvalue {
+                        diff.push(DiffKind::Differs, key, Some(lvalue), Some(rvalue));
+                    }
+                    left_entry = left_iter.next().transpose()?;
+                    right_entry = right_iter.next().transpose()?;
+                }
+            }
+        }
+
+        Ok(diff)
+    }
+
+    fn push(&mut self, kind: DiffKind, key: &[u8], left: Option<&[u8]>, right: Option<&[u8]>) {
+        match kind {
+            DiffKind::OnlyInLeft => self.only_in_left += 1,
+            DiffKind::OnlyInRight => self.only_in_right += 1,
+            DiffKind::Differs => self.differs += 1,
+        }
+        self.entries.push(DiffEntry {
+            kind,
+            key: key.to_vec(),
+            left_value: left.map(<[u8]>::to_vec),
+            right_value: right.map(<[u8]>::to_vec),
+        });
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::escaped_entry::{parse_as_integer, IntMode};
+
+/// The way keys and values are displayed in the table and in the edit window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Stfu8,
+    /// Displayed like STFU-8, the detected format of the values is given in their tooltip.
+    Auto,
+    /// Displayed like STFU-8, the values are also summarized as JSON.
+    Json,
+    /// The values are edited as hexadecimal and displayed decoded.
+    Msgpack,
+    /// The values are edited as hexadecimal and displayed in CBOR diagnostic notation.
+    Cbor,
+    /// The values are edited as hexadecimal and decoded with the Protobuf schema of the database.
+    Protobuf,
+    Hex,
+    Utf8Lossy,
+    IntU32Le,
+    IntU32Be,
+    IntU64Le,
+    IntU64Be,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 12] = [
+        DisplayMode::Stfu8,
+        DisplayMode::Auto,
+        DisplayMode::Json,
+        DisplayMode::Msgpack,
+        DisplayMode::Cbor,
+        DisplayMode::Protobuf,
+        DisplayMode::Hex,
+        DisplayMode::Utf8Lossy,
+        DisplayMode::IntU32Le,
+        DisplayMode::IntU32Be,
+        DisplayMode::IntU64Le,
+        DisplayMode::IntU64Be,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Stfu8 => "STFU-8",
+            DisplayMode::Auto => "Auto-detect",
+            DisplayMode::Json => "JSON",
+            DisplayMode::Msgpack => "MessagePack",
+            DisplayMode::Cbor => "CBOR",
+            DisplayMode::Protobuf => "Protobuf",
+            DisplayMode::Hex => "Hex",
+            DisplayMode::Utf8Lossy => "UTF-8 (lossy)",
+            DisplayMode::IntU32Le => "u32 (little-endian)",
+            DisplayMode::IntU32Be => "u32 (big-endian)",
+            DisplayMode::IntU64Le => "u64 (little-endian)",
+            DisplayMode::IntU64Be => "u64 (big-endian)",
+        }
+    }
+
+    /// The representation of the keys of the integer modes.
+    pub fn int_mode(&self) -> Option<IntMode> {
+        match self {
+            DisplayMode::IntU32Le => Some(IntMode::U32Le),
+            DisplayMode::IntU32Be => Some(IntMode::U32Be),
+            DisplayMode::IntU64Le => Some(IntMode::U64Le),
+            DisplayMode::IntU64Be => Some(IntMode::U64Be),
+            _ => None,
+        }
+    }
+
+    /// Whether decoding the displayed text may not give back the bytes it was encoded from.
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, DisplayMode::Utf8Lossy)
+    }
+
+    /// The binary formats only concern values, keys are displayed as STFU-8.
+    fn key_mode(&self) -> DisplayMode {
+        match self {
+            DisplayMode::Msgpack | DisplayMode::Cbor | DisplayMode::Protobuf => DisplayMode::Stfu8,
+            _ => *self,
+        }
+    }
+
+    /// The integer modes only concern keys, values are displayed as STFU-8.
+    fn value_mode(&self) -> DisplayMode {
+        match self {
+            DisplayMode::IntU32Le
+            | DisplayMode::IntU32Be
+            | DisplayMode::IntU64Le
+            | DisplayMode::IntU64Be => DisplayMode::Stfu8,
+            _ => *self,
+        }
+    }
+
+    /// Encodes the key bytes into a displayable string.
+    ///
+    /// Keys that do not have the size of the integer mode are displayed in hexadecimal.
+    pub fn encode_key(&self, bytes: &[u8]) -> String {
+        self.key_mode().encode(bytes)
+    }
+
+    /// Encodes the value bytes into a displayable string.
+    pub fn encode_value(&self, bytes: &[u8]) -> String {
+        self.value_mode().encode(bytes)
+    }
+
+    /// Decodes a key written in this mode back into bytes.
+    ///
+    /// The lossy UTF-8 mode cannot represent every byte so the text is taken as-is
+    /// and the integer modes only accept decimal or `0x` prefixed hexadecimal integers.
+    pub fn decode_key(&self, text: &str) -> Result<Vec<u8>, DecodeError> {
+        self.key_mode().decode(text)
+    }
+
+    /// Decodes a value written in this mode back into bytes.
+    pub fn decode_value(&self, text: &str) -> Result<Vec<u8>, DecodeError> {
+        self.value_mode().decode(text)
+    }
+
+    fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            DisplayMode::Stfu8 | DisplayMode::Auto | DisplayMode::Json => {
+                stfu8::encode_u8_pretty(bytes)
+            }
+            DisplayMode::Hex | DisplayMode::Msgpack | DisplayMode::Cbor | DisplayMode::Protobuf => {
+                encode_hex(bytes)
+            }
+            DisplayMode::Utf8Lossy => String::from_utf8_lossy(bytes).into_owned(),
+            DisplayMode::IntU32Le => match bytes.try_into() {
+                Ok(bytes) => u32::from_le_bytes(bytes).to_string(),
+                Err(_) => encode_hex(bytes),
+            },
+            DisplayMode::IntU32Be => match bytes.try_into() {
+                Ok(bytes) => u32::from_be_bytes(bytes).to_string(),
+                Err(_) => encode_hex(bytes),
+            },
+            DisplayMode::IntU64Le => match bytes.try_into() {
+                Ok(bytes) => u64::from_le_bytes(bytes).to_string(),
+                Err(_) => encode_hex(bytes),
+            },
+            DisplayMode::IntU64Be => match bytes.try_into() {
+                Ok(bytes) => u64::from_be_bytes(bytes).to_string(),
+                Err(_) => encode_hex(bytes),
+            },
+        }
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, DecodeError> {
+        if let Some(int_mode) = self.int_mode() {
+            return parse_as_integer(text, int_mode);
+        }
+
+        match self {
+            DisplayMode::Stfu8 | DisplayMode::Auto | DisplayMode::Json => {
+                stfu8::decode_u8(text).map_err(DecodeError::Stfu8)
+            }
+            DisplayMode::Hex | DisplayMode::Msgpack | DisplayMode::Cbor | DisplayMode::Protobuf => {
+                decode_hex(text)
+            }
+            DisplayMode::Utf8Lossy => Ok(text.as_bytes().to_vec()),
+            DisplayMode::IntU32Le
+            | DisplayMode::IntU32Be
+            | DisplayMode::IntU64Le
+            | DisplayMode::IntU64Be => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Stfu8(stfu8::DecodeError),
+    InvalidHex(String),
+    /// A character of a plain hexadecimal key or value is not an hexadecimal digit.
+    InvalidHexDigit(char),
+    /// A plain hexadecimal key or value does not have two digits per byte.
+    OddHexLength(usize),
+    InvalidInteger(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Stfu8(e) => write!(f, "{e}"),
+            DecodeError::InvalidHex(byte) => write!(f, "invalid hexadecimal byte {byte:?}"),
+            DecodeError::InvalidHexDigit(c) => {
+                write!(f, "invalid hexadecimal digit {c:?}")
+            }
+            DecodeError::OddHexLength(len) => {
+                write!(f, "odd number of hexadecimal digits ({len})")
+            }
+            DecodeError::InvalidInteger(text) => write!(f, "invalid integer {text:?}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Formats the bytes as space-separated two-digit uppercase hexadecimal.
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            output.push(' ');
+        }
+        output.push_str(&format!("{byte:02X}"));
+    }
+    output
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, DecodeError> {
+    text.split_whitespace()
+        .map(|byte| {
+            if byte.len() <= 2 && byte.bytes().all(|b| b.is_ascii_hexdigit()) {
+                Ok(u8::from_str_radix(byte, 16).unwrap())
+            } else {
+                Err(DecodeError::InvalidHex(byte.to_owned()))
+            }
+        })
+        .collect()
+}
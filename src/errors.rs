@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use eframe::egui::{self, Color32};
+
+/// A failed database operation, displayed as a banner until dismissed.
+#[derive(Debug)]
+pub struct AppError {
+    /// What we were doing, e.g. `Could not delete the entry`.
+    pub context: String,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(context: impl Into<String>, error: impl fmt::Display) -> AppError {
+        AppError { context: context.into(), message: error.to_string() }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.message)
+    }
+}
+
+/// Queues the error of a result instead of unwrapping it.
+pub trait ReportError<T> {
+    fn report(self, errors: &mut VecDeque<AppError>, context: &str) -> Option<T>;
+}
+
+impl<T, E: fmt::Display> ReportError<T> for Result<T, E> {
+    fn report(self, errors: &mut VecDeque<AppError>, context: &str) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(e) => {
+                errors.push_back(AppError::new(context, e));
+                None
+            }
+        }
+    }
+}
+
+/// Displays the pending errors as red banners, the oldest first.
+pub fn errors_ui(ui: &mut egui::Ui, errors: &mut VecDeque<AppError>) {
+    let mut dismissed = None;
+    for (i, error) in errors.iter().enumerate() {
+        egui::Frame::none().fill(Color32::DARK_RED).inner_margin(4.0).rounding(2.0).show(
+            ui,
+            |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::WHITE, error.to_string());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                            dismissed = Some(i);
+                        }
+                    });
+                });
+            },
+        );
+    }
+    if let Some(i) = dismissed {
+        errors.remove(i);
+    }
+}
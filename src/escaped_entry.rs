@@ -1,7 +1,22 @@
+use crate::display_mode::{DecodeError, DisplayMode};
+
+/// The byte representations of the integer keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntMode {
+    U32Le,
+    U32Be,
+    U64Le,
+    U64Be,
+}
+
 #[derive(Debug, Default)]
 pub struct EscapedEntry {
     pub key: String,
     pub data: String,
+    /// Whether the key is written as plain hexadecimal instead of in the display mode.
+    pub key_hex: bool,
+    /// Whether the data is written as plain hexadecimal instead of in the display mode.
+    pub data_hex: bool,
 }
 
 impl EscapedEntry {
@@ -10,11 +25,132 @@ impl EscapedEntry {
         self.data.clear();
     }
 
-    pub fn decoded_key(&self) -> Result<Vec<u8>, stfu8::DecodeError> {
-        stfu8::decode_u8(&self.key)
+    /// Replaces the key and data by ones written in the display mode.
+    pub fn prefill(&mut self, key: String, data: String) {
+        *self = EscapedEntry { key, data, key_hex: false, data_hex: false };
+    }
+
+    /// Decodes the key in the display mode, or as plain hexadecimal when `key_hex` is set.
+    pub fn decoded_key(&self, mode: DisplayMode) -> Result<Vec<u8>, DecodeError> {
+        if self.key_hex {
+            decode_plain_hex(&self.key)
+        } else {
+            mode.decode_key(&self.key)
+        }
+    }
+
+    /// Decodes the value in the display mode, or as plain hexadecimal when `data_hex` is set.
+    pub fn decoded_data(&self, mode: DisplayMode) -> Result<Vec<u8>, DecodeError> {
+        if self.data_hex {
+            decode_plain_hex(&self.data)
+        } else {
+            mode.decode_value(&self.data)
+        }
+    }
+}
+
+/// Parses a decimal or `0x` prefixed hexadecimal integer into the bytes of the mode.
+///
+/// The sign comes before the prefix, e.g. `-0x10`. The 64 bits modes also accept
+/// negative integers, stored in two's complement.
+pub fn parse_as_integer(s: &str, mode: IntMode) -> Result<Vec<u8>, DecodeError> {
+    let s = s.trim();
+    let invalid_integer = || DecodeError::InvalidInteger(s.to_owned());
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (radix, digits) = match unsigned.strip_prefix("0x").or(unsigned.strip_prefix("0X")) {
+        Some(hex) => (16, hex),
+        None => (10, unsigned),
+    };
+    // `from_str_radix` accepts a sign of its own, e.g. `0x-1`.
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(invalid_integer());
+    }
+    let n = i128::from_str_radix(digits, radix).map_err(|_| invalid_integer())?;
+    let n = if negative { -n } else { n };
+
+    match mode {
+        IntMode::U32Le => u32::try_from(n).map(|n| n.to_le_bytes().to_vec()),
+        IntMode::U32Be => u32::try_from(n).map(|n| n.to_be_bytes().to_vec()),
+        IntMode::U64Le => u64::try_from(n)
+            .or_else(|_| i64::try_from(n).map(|n| n as u64))
+            .map(|n| n.to_le_bytes().to_vec()),
+        IntMode::U64Be => u64::try_from(n)
+            .or_else(|_| i64::try_from(n).map(|n| n as u64))
+            .map(|n| n.to_be_bytes().to_vec()),
+    }
+    .map_err(|_| invalid_integer())
+}
+
+/// Decodes unseparated pairs of hexadecimal digits with an optional `0x` prefix, e.g. `0xdeadBEEF`.
+fn decode_plain_hex(hex: &str) -> Result<Vec<u8>, DecodeError> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHexDigit(c));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(DecodeError::OddHexLength(hex.len()));
+    }
+    Ok((0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_as_integer, IntMode};
+
+    fn parse(s: &str, mode: IntMode) -> Option<Vec<u8>> {
+        parse_as_integer(s, mode).ok()
+    }
+
+    #[test]
+    fn u32_modes() {
+        assert_eq!(parse("258", IntMode::U32Le), Some(vec![2, 1, 0, 0]));
+        assert_eq!(parse("258", IntMode::U32Be), Some(vec![0, 0, 1, 2]));
+        assert_eq!(parse(" 0x102 ", IntMode::U32Be), Some(vec![0, 0, 1, 2]));
+        assert_eq!(parse("0X102", IntMode::U32Le), Some(vec![2, 1, 0, 0]));
+        assert_eq!(parse("+7", IntMode::U32Be), Some(vec![0, 0, 0, 7]));
+        assert_eq!(parse("4294967295", IntMode::U32Le), Some(vec![0xff; 4]));
+        assert_eq!(parse("0xffffffff", IntMode::U32Be), Some(vec![0xff; 4]));
+
+        for mode in [IntMode::U32Le, IntMode::U32Be] {
+            assert_eq!(parse("4294967296", mode), None);
+            assert_eq!(parse("0x100000000", mode), None);
+            assert_eq!(parse("-1", mode), None);
+            assert_eq!(parse("-0x1", mode), None);
+        }
+    }
+
+    #[test]
+    fn u64_modes() {
+        assert_eq!(parse("258", IntMode::U64Le), Some(vec![2, 1, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(parse("0X102", IntMode::U64Be), Some(vec![0, 0, 0, 0, 0, 0, 1, 2]));
+        assert_eq!(parse("18446744073709551615", IntMode::U64Le), Some(vec![0xff; 8]));
+        assert_eq!(parse("-1", IntMode::U64Le), Some(vec![0xff; 8]));
+        assert_eq!(
+            parse("-0x2", IntMode::U64Be),
+            Some(vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe])
+        );
+        assert_eq!(
+            parse("-9223372036854775808", IntMode::U64Be),
+            Some(vec![0x80, 0, 0, 0, 0, 0, 0, 0])
+        );
+
+        for mode in [IntMode::U64Le, IntMode::U64Be] {
+            assert_eq!(parse("18446744073709551616", mode), None);
+            assert_eq!(parse("0x10000000000000000", mode), None);
+            assert_eq!(parse("-9223372036854775809", mode), None);
+        }
     }
 
-    pub fn decoded_data(&self) -> Result<Vec<u8>, stfu8::DecodeError> {
-        stfu8::decode_u8(&self.data)
+    #[test]
+    fn invalid_integers() {
+        for mode in [IntMode::U32Le, IntMode::U32Be, IntMode::U64Le, IntMode::U64Be] {
+            for s in ["", "0x", "-", "0x-1", "0x+1", "--1", "-+1", "1.5", "0xg", "12a", "0b1"] {
+                assert_eq!(parse(s, mode), None, "{s:?} was accepted");
+            }
+        }
     }
 }
@@ -0,0 +1,354 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+use serde::{Deserialize, Serialize};
+
+/// The file formats the database entries can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Csv,
+    Yaml,
+    Ndjson,
+}
+
+impl FileFormat {
+    pub const ALL: [FileFormat; 4] =
+        [FileFormat::Json, FileFormat::Csv, FileFormat::Yaml, FileFormat::Ndjson];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Json => "JSON",
+            FileFormat::Csv => "CSV",
+            FileFormat::Yaml => "YAML",
+            FileFormat::Ndjson => "NDJSON",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Json => "json",
+            FileFormat::Csv => "csv",
+            FileFormat::Yaml => "yaml",
+            FileFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// Writes every entry of the database into the file and returns the number of entries.
+    pub fn export(
+        &self,
+        database: Database<Bytes, Bytes>,
+        rtxn: &RoTxn,
+        path: &Path,
+        progress: &AtomicUsize,
+    ) -> anyhow::Result<usize> {
+        self.write_entries(database.iter(rtxn)?, path, progress)
+    }
+
+    /// Writes the entries of the keys, in this order, and returns the number of entries.
+    ///
+    /// The keys that are no longer in the database are ignored.
+    pub fn export_keys(
+        &self,
+        database: Database<Bytes, Bytes>,
+        rtxn: &RoTxn,
+        keys: &[Vec<u8>],
+        path: &Path,
+        progress: &AtomicUsize,
+    ) -> anyhow::Result<usize> {
+        let entries = keys.iter().filter_map(|key| match database.get(rtxn, key) {
+            Ok(Some(value)) => Some(Ok((key.as_slice(), value))),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        });
+        self.write_entries(entries, path, progress)
+    }
+
+    fn write_entries<'a>(
+        &self,
+        entries: impl Iterator<Item = heed::Result<(&'a [u8], &'a [u8])>>,
+        path: &Path,
+        progress: &AtomicUsize,
+    ) -> anyhow::Result<usize> {
+        match self {
+            FileFormat::Json => export_json(entries, path, progress),
+            FileFormat::Csv => export_csv(entries, path, progress),
+            FileFormat::Yaml => export_yaml(entries, path, progress),
+            FileFormat::Ndjson => export_ndjson(entries, path, progress),
+        }
+    }
+}
+
+/// The representation of keys and values in the JSON format.
+///
+/// Valid UTF-8 is written as a plain string, anything else is written as hexadecimal.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonBytes {
+    Utf8(String),
+    Hex {
+        #[serde(rename = "$hex", with = "hex_string")]
+        hex: Vec<u8>,
+    },
+}
+
+impl JsonBytes {
+    pub fn from_bytes(bytes: &[u8]) -> JsonBytes {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => JsonBytes::Utf8(s.to_owned()),
+            Err(_) => JsonBytes::Hex { hex: bytes.to_vec() },
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            JsonBytes::Utf8(s) => s.into_bytes(),
+            JsonBytes::Hex { hex } => hex,
+        }
+    }
+}
+
+/// (De)serializes bytes as a lowercase hexadecimal string without separators.
+mod hex_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(D::Error::custom(format!("invalid hexadecimal string {hex:?}")));
+        }
+        Ok((0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonEntry {
+    pub key: JsonBytes,
+    pub value: JsonBytes,
+}
+
+/// Writes every entry as a JSON array into the file.
+fn export_json<'a>(
+    entries: impl Iterator<Item = heed::Result<(&'a [u8], &'a [u8])>>,
+    path: &Path,
+    progress: &AtomicUsize,
+) -> anyhow::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(b"[")?;
+    let mut count = 0;
+    for result in entries {
+        let (key, value) = result?;
+        if count != 0 {
+            writer.write_all(b",")?;
+        }
+        writer.write_all(b"\n  ")?;
+        let entry =
+            JsonEntry { key: JsonBytes::from_bytes(key), value: JsonBytes::from_bytes(value) };
+        serde_json::to_writer(&mut writer, &entry)?;
+        count += 1;
+        progress.store(count, Ordering::Relaxed);
+    }
+    writer.write_all(b"\n]\n")?;
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// An entry on a line of the NDJSON format, the short names keep the lines small.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NdjsonEntry {
+    #[serde(rename = "k")]
+    pub key: JsonBytes,
+    #[serde(rename = "v")]
+    pub value: JsonBytes,
+}
+
+/// Writes every entry as a JSON object on its own line, readable by `jq` line by line.
+fn export_ndjson<'a>(
+    entries: impl Iterator<Item = heed::Result<(&'a [u8], &'a [u8])>>,
+    path: &Path,
+    progress: &AtomicUsize,
+) -> anyhow::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let mut count = 0;
+    for result in entries {
+        let (key, value) = result?;
+        let entry =
+            NdjsonEntry { key: JsonBytes::from_bytes(key), value: JsonBytes::from_bytes(value) };
+        serde_json::to_writer(&mut writer, &entry)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+        progress.store(count, Ordering::Relaxed);
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Writes every entry as a `key,value` CSV row into the file.
+///
+/// Valid UTF-8 is written as-is, anything else is written as STFU-8. UTF-8 containing
+/// a backslash is also written as STFU-8 to be able to tell them apart when importing.
+fn export_csv<'a>(
+    entries: impl Iterator<Item = heed::Result<(&'a [u8], &'a [u8])>>,
+    path: &Path,
+    progress: &AtomicUsize,
+) -> anyhow::Result<usize> {
+    let mut writer =
+        csv::WriterBuilder::new().quote_style(csv::QuoteStyle::Always).from_path(path)?;
+
+    writer.write_record(["key", "value"])?;
+    let mut count = 0;
+    for result in entries {
+        let (key, value) = result?;
+        writer.write_record([csv_field(key), csv_field(value)])?;
+        count += 1;
+        progress.store(count, Ordering::Relaxed);
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+fn csv_field(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.contains('\\') => s.to_owned(),
+        _ => stfu8::encode_u8_pretty(bytes),
+    }
+}
+
+/// YAML only allows single-line implicit keys up to this length, longer keys are explicit.
+const MAX_YAML_IMPLICIT_KEY_LEN: usize = 1024;
+
+/// Writes every entry as a YAML mapping from the STFU-8 keys to the values.
+///
+/// Valid UTF-8 values are written as STFU-8 strings, anything else as base64 tagged `!!binary`.
+fn export_yaml<'a>(
+    entries: impl Iterator<Item = heed::Result<(&'a [u8], &'a [u8])>>,
+    path: &Path,
+    progress: &AtomicUsize,
+) -> anyhow::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let mut count = 0;
+    for result in entries {
+        let (key, value) = result?;
+        let key = yaml_string(&stfu8::encode_u8(key))?;
+        let value = match std::str::from_utf8(value) {
+            Ok(_) => yaml_string(&stfu8::encode_u8(value))?,
+            Err(_) => format!("!!binary {}", BASE64_STANDARD.encode(value)),
+        };
+        if key.len() <= MAX_YAML_IMPLICIT_KEY_LEN {
+            writeln!(writer, "{key}: {value}")?;
+        } else {
+            writeln!(writer, "? {key}\n: {value}")?;
+        }
+        count += 1;
+        progress.store(count, Ordering::Relaxed);
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Formats the string as a YAML scalar, quoted when it would be read as something else.
+///
+/// The strings must be encoded with the non-pretty STFU-8 which escapes the tabs and
+/// line breaks, the scalar then stays on the single line of its entry.
+fn yaml_string(s: &str) -> serde_yaml::Result<String> {
+    Ok(serde_yaml::to_string(s)?.trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::{export_csv, JsonBytes, JsonEntry};
+    use crate::import::csv_field_bytes;
+
+    fn parse_entries(json: &str) -> serde_json::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let entries: Vec<JsonEntry> = serde_json::from_str(json)?;
+        Ok(entries.into_iter().map(|e| (e.key.into_bytes(), e.value.into_bytes())).collect())
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let json = serde_json::to_string(&JsonBytes::from_bytes(b"\x00\xffbinary")).unwrap();
+        assert_eq!(json, r#"{"$hex":"00ff62696e617279"}"#);
+        let bytes: JsonBytes = serde_json::from_str(&json).unwrap();
+        assert_eq!(bytes.into_bytes(), b"\x00\xffbinary");
+
+        let json = serde_json::to_string(&JsonBytes::from_bytes(b"plain")).unwrap();
+        assert_eq!(json, r#""plain""#);
+    }
+
+    #[test]
+    fn hex_mixed_with_plain_strings() {
+        let entries = parse_entries(
+            r#"[
+                {"key": "plain", "value": {"$hex": "00FFab"}},
+                {"key": {"$hex": ""}, "value": "{\"$hex\": \"00\"}"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            [
+                (b"plain".to_vec(), b"\x00\xff\xab".to_vec()),
+                (Vec::new(), br#"{"$hex": "00"}"#.to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_invalid() {
+        for hex in ["abc", "0g", "zz", "+1", "é0"] {
+            let json = format!(r#"[{{"key": "key", "value": {{"$hex": "{hex}"}}}}]"#);
+            assert!(parse_entries(&json).is_err(), "{hex:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let entries: &[(&[u8], &[u8])] = &[
+            (b"comma,key", b"line\r\nbreak"),
+            (b"say \"hi\"", b"\xff\x00binary"),
+            (b"back\\slash", b"\\x41 is not an escape"),
+            (b"empty", b""),
+        ];
+
+        let path = std::env::temp_dir().join(format!("csv_round_trip_{}.csv", std::process::id()));
+        let progress = AtomicUsize::new(0);
+        let count = export_csv(entries.iter().map(|&entry| Ok(entry)), &path, &progress).unwrap();
+        assert_eq!(count, entries.len());
+
+        let mut reader = csv::ReaderBuilder::new().has_headers(true).from_path(&path).unwrap();
+        let imported: Vec<_> = reader
+            .records()
+            .map(|record| {
+                let record = record.unwrap();
+                (csv_field_bytes(&record[0]), csv_field_bytes(&record[1]))
+            })
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected: Vec<_> = entries.iter().map(|(k, v)| (k.to_vec(), v.to_vec())).collect();
+        assert_eq!(imported, expected);
+    }
+}
@@ -0,0 +1,286 @@
+use eframe::egui;
+use egui::Color32;
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+use regex::bytes::Regex;
+
+use crate::errors::ReportError;
+use crate::txn::Txn;
+use crate::{TreeBehavior, READ_ONLY_HINT};
+
+/// The number of matches after which the search stops.
+const MAX_MATCHES: usize = 10_000;
+
+/// How the pattern is searched in the values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The STFU-8 decoded pattern is searched as-is.
+    Exact,
+    /// The pattern is a regex over the value bytes, the replacement can refer to its groups.
+    Regex,
+}
+
+/// A pane searching a pattern in the values of the opened databases to replace it.
+pub struct FindReplace {
+    pattern: String,
+    replacement: String,
+    mode: MatchMode,
+    results: Vec<FindMatch>,
+    /// Whether the search stopped after `MAX_MATCHES` matches.
+    truncated: bool,
+    error: Option<String>,
+    /// Whether the list of the keys to replace is displayed for confirmation.
+    confirming: bool,
+}
+
+/// A value containing the pattern, along with what it becomes once replaced.
+struct FindMatch {
+    database_name: Option<String>,
+    database: Database<Bytes, Bytes>,
+    key: Vec<u8>,
+    old_value: Vec<u8>,
+    new_value: Vec<u8>,
+}
+
+/// The compiled pattern and replacement.
+enum Matcher {
+    Exact { pattern: Vec<u8>, replacement: Vec<u8> },
+    Regex { regex: Regex, replacement: Vec<u8> },
+}
+
+impl Matcher {
+    fn new(mode: MatchMode, pattern: &str, replacement: &str) -> Result<Matcher, String> {
+        match mode {
+            MatchMode::Exact => {
+                let pattern = stfu8::decode_u8(pattern).map_err(|e| format!("pattern: {e}"))?;
+                if pattern.is_empty() {
+                    return Err("the pattern is empty".to_owned());
+                }
+                let replacement =
+                    stfu8::decode_u8(replacement).map_err(|e| format!("replacement: {e}"))?;
+                Ok(Matcher::Exact { pattern, replacement })
+            }
+            MatchMode::Regex => {
+                let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+                Ok(Matcher::Regex { regex, replacement: replacement.as_bytes().to_vec() })
+            }
+        }
+    }
+
+    /// Returns the value with every match replaced, none when there is no match.
+    fn replace(&self, value: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Matcher::Exact { pattern, replacement } => {
+                let mut output = Vec::new();
+                let mut rest = value;
+                let mut found = false;
+                while let Some(i) = rest.windows(pattern.len()).position(|w| w == &pattern[..]) {
+                    output.extend_from_slice(&rest[..i]);
+                    output.extend_from_slice(replacement);
+                    rest = &rest[i + pattern.len()..];
+                    found = true;
+                }
+                output.extend_from_slice(rest);
+                found.then_some(output)
+            }
+            Matcher::Regex { regex, replacement } => regex
+                .is_match(value)
+                .then(|| regex.replace_all(value, replacement.as_slice()).into_owned()),
+        }
+    }
+}
+
+impl Default for FindReplace {
+    fn default() -> FindReplace {
+        FindReplace {
+            pattern: String::new(),
+            replacement: String::new(),
+            mode: MatchMode::Exact,
+            results: Vec::new(),
+            truncated: false,
+            error: None,
+            confirming: false,
+        }
+    }
+}
+
+impl FindReplace {
+    pub fn ui(&mut self, behavior: &mut TreeBehavior, ui: &mut egui::Ui) {
+        let FindReplace { pattern, replacement, mode, results, truncated, error, confirming } =
+            self;
+
+        egui::Grid::new("find_replace").num_columns(2).show(ui, |ui| {
+            ui.label("Find");
+            ui.add(egui::TextEdit::singleline(pattern).hint_text("pattern"));
+            ui.end_row();
+            ui.label("Replace with");
+            ui.add(egui::TextEdit::singleline(replacement).hint_text(match mode {
+                MatchMode::Exact => "replacement",
+                MatchMode::Regex => "replacement, $1 refers to the first group",
+            }));
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(mode, MatchMode::Exact, "Exact (STFU-8)");
+            ui.radio_value(mode, MatchMode::Regex, "Regex");
+        });
+
+        ui.horizontal(|ui| {
+            let find = ui
+                .button("Find")
+                .on_hover_text("Searches the values of the databases displayed in a tab");
+            if find.clicked() {
+                results.clear();
+                *truncated = false;
+                *error = None;
+                match Matcher::new(*mode, pattern, replacement) {
+                    Ok(matcher) => {
+                        let rtxn = behavior.txn.read_txn();
+                        let found = find_matches(behavior.open_databases, rtxn, &matcher);
+                        match found {
+                            Ok((found, stopped)) => {
+                                *results = found;
+                                *truncated = stopped;
+                            }
+                            Err(e) => *error = Some(e.to_string()),
+                        }
+                    }
+                    Err(e) => *error = Some(e),
+                }
+            }
+
+            let replace = ui
+                .add_enabled(
+                    !results.is_empty() && !behavior.read_only,
+                    egui::Button::new("Replace all"),
+                )
+                .on_disabled_hover_text(READ_ONLY_HINT);
+            if replace.clicked() {
+                *confirming = true;
+            }
+        });
+
+        if let Some(error) = error {
+            ui.colored_label(Color32::RED, error.as_str());
+        }
+        if *truncated {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!("The search stopped after {MAX_MATCHES} matches"),
+            );
+        }
+
+        ui.separator();
+        ui.label(format!("{} matching values", results.len()));
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("find_replace_results").num_columns(3).striped(true).show(ui, |ui| {
+                for found in results.iter() {
+                    ui.label(found.database_name.as_deref().unwrap_or("{main}"));
+                    ui.label(stfu8::encode_u8_pretty(&found.key));
+                    ui.label(stfu8::encode_u8_pretty(&found.old_value)).on_hover_text(format!(
+                        "Replaced by {}",
+                        stfu8::encode_u8_pretty(&found.new_value)
+                    ));
+                    ui.end_row();
+                }
+            });
+        });
+
+        if *confirming {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new("Replace all")
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("The values of these {} keys will be replaced:", results.len()));
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for found in results.iter() {
+                            ui.label(format!(
+                                "{}: {}",
+                                found.database_name.as_deref().unwrap_or("{main}"),
+                                stfu8::encode_u8_pretty(&found.key)
+                            ));
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let replace = ui
+                            .add_enabled(!behavior.read_only, egui::Button::new("Replace"))
+                            .on_disabled_hover_text(READ_ONLY_HINT);
+                        if replace.clicked() {
+                            let env = behavior.env;
+                            behavior.txn.start_write(env).report(behavior.errors, "Could not start writing");
+                            if let Txn::Rw(ref mut wtxn) = behavior.txn {
+                                let (mut replaced, mut skipped) = (0, 0);
+                                for found in results.drain(..) {
+                                    // The value changed since the search, the replacement is stale.
+                                    let current = found
+                                        .database
+                                        .get(wtxn, &found.key)
+                                        .report(behavior.errors, "Could not read the value");
+                                    if current != Some(Some(&found.old_value[..])) {
+                                        skipped += 1;
+                                        continue;
+                                    }
+                                    let put = behavior
+                                        .history
+                                        .put(
+                                            wtxn,
+                                            &found.database_name,
+                                            found.database,
+                                            &found.key,
+                                            &found.new_value,
+                                        )
+                                        .report(behavior.errors, "Could not replace the value");
+                                    replaced += usize::from(put.is_some());
+                                }
+                                let message = match skipped {
+                                    0 => format!("Replaced {replaced} values"),
+                                    skipped => format!(
+                                        "Replaced {replaced} values, skipped {skipped} that changed since the search"
+                                    ),
+                                };
+                                behavior.toasts.add(message);
+                            }
+                            done = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            done = true;
+                        }
+                    });
+                });
+            if !open || done {
+                *confirming = false;
+            }
+        }
+    }
+}
+
+/// Collects the values of the databases that match, stops after `MAX_MATCHES` matches.
+fn find_matches(
+    databases: &[(Option<String>, Database<Bytes, Bytes>)],
+    rtxn: &RoTxn,
+    matcher: &Matcher,
+) -> heed::Result<(Vec<FindMatch>, bool)> {
+    let mut results = Vec::new();
+    for (database_name, database) in databases {
+        for result in database.iter(rtxn)? {
+            let (key, value) = result?;
+            if let Some(new_value) = matcher.replace(value) {
+                if results.len() == MAX_MATCHES {
+                    return Ok((results, true));
+                }
+                results.push(FindMatch {
+                    database_name: database_name.clone(),
+                    database: *database,
+                    key: key.to_vec(),
+                    old_value: value.to_vec(),
+                    new_value,
+                });
+            }
+        }
+    }
+    Ok((results, false))
+}
@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use anyhow::Context;
+use eframe::egui::Color32;
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+
+use crate::display_mode::DisplayMode;
+
+/// The format of a value guessed from its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedFormat {
+    Utf8,
+    Json,
+    Msgpack,
+    Binary,
+}
+
+impl DetectedFormat {
+    pub const ALL: [DetectedFormat; 4] = [
+        DetectedFormat::Utf8,
+        DetectedFormat::Json,
+        DetectedFormat::Msgpack,
+        DetectedFormat::Binary,
+    ];
+
+    /// The translucent background of the values of this format in the auto-detect mode.
+    pub fn color(&self) -> Color32 {
+        match self {
+            DetectedFormat::Utf8 => Color32::from_rgba_unmultiplied(0, 200, 0, 24),
+            DetectedFormat::Json => Color32::from_rgba_unmultiplied(0, 120, 255, 32),
+            DetectedFormat::Msgpack => Color32::from_rgba_unmultiplied(255, 140, 0, 32),
+            DetectedFormat::Binary => Color32::from_rgba_unmultiplied(128, 128, 128, 32),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DetectedFormat::Utf8 => "UTF-8",
+            DetectedFormat::Json => "JSON",
+            DetectedFormat::Msgpack => "MessagePack",
+            DetectedFormat::Binary => "Binary",
+        }
+    }
+}
+
+/// Guesses the format of the bytes.
+///
+/// JSON objects are recognized before plain UTF-8 as they are also valid UTF-8.
+/// MessagePack has no magic bytes, we only recognize values starting with a map
+/// or an array marker as they are the most common top-level values.
+pub fn detect_format(bytes: &[u8]) -> DetectedFormat {
+    if bytes.first() == Some(&b'{') && serde_json::from_slice::<serde_json::Value>(bytes).is_ok() {
+        DetectedFormat::Json
+    } else if std::str::from_utf8(bytes).is_ok() {
+        DetectedFormat::Utf8
+    } else if bytes.first().is_some_and(|&b| is_msgpack_container_marker(b)) {
+        DetectedFormat::Msgpack
+    } else {
+        DetectedFormat::Binary
+    }
+}
+
+/// Summarizes a JSON value on a single line, objects and arrays only show their length.
+pub fn json_summary(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => format!("{{{} keys}}", map.len()),
+        serde_json::Value::Array(array) => format!("[{} items]", array.len()),
+        other => other.to_string(),
+    }
+}
+
+/// Whether the byte is the marker of a MessagePack map or array.
+fn is_msgpack_container_marker(byte: u8) -> bool {
+    matches!(byte, 0x80..=0x9f | 0xdc..=0xdf)
+}
+
+/// A value decoded from a structured format.
+#[derive(Debug)]
+pub struct StructuredValue {
+    /// A short description of the value, displayed as a badge.
+    pub summary: String,
+    /// The text to display in the table instead of the raw value, if any.
+    pub text: Option<String>,
+    /// The value formatted on multiple lines with two-space indentation.
+    pub pretty: String,
+}
+
+/// Decodes the value when the display mode or the detected format is a structured one.
+///
+/// Returns the name of the format along with the decoded value or the decoding error.
+pub fn decode_structured(
+    mode: DisplayMode,
+    proto_message: Option<&MessageDescriptor>,
+    bytes: &[u8],
+) -> Option<(&'static str, Result<StructuredValue, String>)> {
+    let format = match mode {
+        DisplayMode::Protobuf => return Some(("Protobuf", decode_protobuf(proto_message, bytes))),
+        DisplayMode::Json => DetectedFormat::Json,
+        DisplayMode::Msgpack => DetectedFormat::Msgpack,
+        DisplayMode::Cbor => return Some(("CBOR", decode_cbor(bytes))),
+        DisplayMode::Auto => detect_format(bytes),
+        _ => return None,
+    };
+
+    match format {
+        DetectedFormat::Json => Some(("JSON", decode_json(bytes))),
+        DetectedFormat::Msgpack => Some(("MessagePack", decode_msgpack(bytes))),
+        DetectedFormat::Utf8 | DetectedFormat::Binary => None,
+    }
+}
+
+pub fn decode_json(bytes: &[u8]) -> Result<StructuredValue, String> {
+    let value: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+    Ok(StructuredValue {
+        summary: json_summary(&value),
+        text: None,
+        pretty: serde_json::to_string_pretty(&value).unwrap(),
+    })
+}
+
+pub fn decode_msgpack(bytes: &[u8]) -> Result<StructuredValue, String> {
+    let mut reader = bytes;
+    let value = rmpv::decode::read_value(&mut reader).map_err(|e| e.to_string())?;
+    if !reader.is_empty() {
+        return Err(format!("{} trailing bytes after the value", reader.len()));
+    }
+
+    let mut pretty = String::new();
+    write_msgpack_pretty(&value, 0, &mut pretty);
+    Ok(StructuredValue {
+        summary: msgpack_type_name(&value).to_owned(),
+        text: Some(value.to_string()),
+        pretty,
+    })
+}
+
+fn msgpack_type_name(value: &rmpv::Value) -> &'static str {
+    match value {
+        rmpv::Value::Nil => "nil",
+        rmpv::Value::Boolean(_) => "bool",
+        rmpv::Value::Integer(_) => "int",
+        rmpv::Value::F32(_) | rmpv::Value::F64(_) => "float",
+        rmpv::Value::String(_) => "str",
+        rmpv::Value::Binary(_) => "bin",
+        rmpv::Value::Array(_) => "array",
+        rmpv::Value::Map(_) => "map",
+        rmpv::Value::Ext(..) => "ext",
+    }
+}
+
+/// Writes the MessagePack value with one array element or map entry per line.
+fn write_msgpack_pretty(value: &rmpv::Value, indent: usize, out: &mut String) {
+    let padding = "  ".repeat(indent + 1);
+    match value {
+        rmpv::Value::Array(array) if !array.is_empty() => {
+            out.push_str("[\n");
+            for (i, value) in array.iter().enumerate() {
+                out.push_str(&padding);
+                write_msgpack_pretty(value, indent + 1, out);
+                out.push_str(if i + 1 == array.len() { "\n" } else { ",\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        }
+        rmpv::Value::Map(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            for (i, (key, value)) in map.iter().enumerate() {
+                out.push_str(&padding);
+                write_msgpack_pretty(key, indent + 1, out);
+                out.push_str(": ");
+                write_msgpack_pretty(value, indent + 1, out);
+                out.push_str(if i + 1 == map.len() { "\n" } else { ",\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn decode_cbor(bytes: &[u8]) -> Result<StructuredValue, String> {
+    let value: ciborium::Value = ciborium::de::from_reader(bytes).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    write_cbor(&value, None, &mut text);
+    let mut pretty = String::new();
+    write_cbor(&value, Some(0), &mut pretty);
+    Ok(StructuredValue { summary: cbor_type_name(&value).to_owned(), text: Some(text), pretty })
+}
+
+fn cbor_type_name(value: &ciborium::Value) -> &'static str {
+    match value {
+        ciborium::Value::Integer(_) => "int",
+        ciborium::Value::Bytes(_) => "bytes",
+        ciborium::Value::Float(_) => "float",
+        ciborium::Value::Text(_) => "text",
+        ciborium::Value::Bool(_) => "bool",
+        ciborium::Value::Null => "null",
+        ciborium::Value::Tag(..) => "tag",
+        ciborium::Value::Array(_) => "array",
+        ciborium::Value::Map(_) => "map",
+        _ => "unknown",
+    }
+}
+
+/// Writes the CBOR value in diagnostic notation (RFC 8949 §8).
+///
+/// The value is written on a single line without indentation level, otherwise
+/// with one array element or map entry per line.
+fn write_cbor(value: &ciborium::Value, indent: Option<usize>, out: &mut String) {
+    use std::fmt::Write;
+
+    let (open_sep, sep, close_sep) = match indent {
+        Some(indent) => (
+            format!("\n{}", "  ".repeat(indent + 1)),
+            format!(",\n{}", "  ".repeat(indent + 1)),
+            format!("\n{}", "  ".repeat(indent)),
+        ),
+        None => (String::new(), ", ".to_owned(), String::new()),
+    };
+    let inner = indent.map(|indent| indent + 1);
+
+    match value {
+        ciborium::Value::Integer(integer) => write!(out, "{}", i128::from(*integer)).unwrap(),
+        ciborium::Value::Bytes(bytes) => {
+            out.push_str("h'");
+            bytes.iter().for_each(|b| write!(out, "{b:02x}").unwrap());
+            out.push('\'');
+        }
+        ciborium::Value::Float(float) if float.is_nan() => out.push_str("NaN"),
+        ciborium::Value::Float(float) if float.is_infinite() => {
+            out.push_str(if *float > 0.0 { "Infinity" } else { "-Infinity" })
+        }
+        ciborium::Value::Float(float) => write!(out, "{float:?}").unwrap(),
+        ciborium::Value::Text(text) => write!(out, "{text:?}").unwrap(),
+        ciborium::Value::Bool(b) => write!(out, "{b}").unwrap(),
+        ciborium::Value::Null => out.push_str("null"),
+        ciborium::Value::Tag(tag, value) => {
+            write!(out, "{tag}(").unwrap();
+            write_cbor(value, indent, out);
+            out.push(')');
+        }
+        ciborium::Value::Array(array) if !array.is_empty() => {
+            out.push('[');
+            out.push_str(&open_sep);
+            for (i, value) in array.iter().enumerate() {
+                if i != 0 {
+                    out.push_str(&sep);
+                }
+                write_cbor(value, inner, out);
+            }
+            out.push_str(&close_sep);
+            out.push(']');
+        }
+        ciborium::Value::Array(_) => out.push_str("[]"),
+        ciborium::Value::Map(map) if !map.is_empty() => {
+            out.push('{');
+            out.push_str(&open_sep);
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i != 0 {
+                    out.push_str(&sep);
+                }
+                write_cbor(key, inner, out);
+                out.push_str(": ");
+                write_cbor(value, inner, out);
+            }
+            out.push_str(&close_sep);
+            out.push('}');
+        }
+        ciborium::Value::Map(_) => out.push_str("{}"),
+        _ => out.push_str("undefined"),
+    }
+}
+
+/// Compiles the `.proto` file and returns the descriptor of the message.
+///
+/// The directory of the file is used to resolve the imports.
+pub fn load_proto_message(path: &Path, message_name: &str) -> anyhow::Result<MessageDescriptor> {
+    let file_name = path.file_name().context("the schema path is not a file")?;
+    let include = path.parent().unwrap_or(Path::new("."));
+    let pool = protox::Compiler::new([include])?.open_file(file_name)?.descriptor_pool();
+    pool.get_message_by_name(message_name)
+        .with_context(|| format!("{message_name} is not declared in {}", path.display()))
+}
+
+fn decode_protobuf(
+    message: Option<&MessageDescriptor>,
+    bytes: &[u8],
+) -> Result<StructuredValue, String> {
+    let message = message.ok_or("no Protobuf schema configured for this database")?;
+    let decoded = DynamicMessage::decode(message.clone(), bytes).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(&decoded).map_err(|e| e.to_string())?;
+    Ok(StructuredValue {
+        summary: message.name().to_owned(),
+        text: Some(value.to_string()),
+        pretty: serde_json::to_string_pretty(&value).unwrap(),
+    })
+}
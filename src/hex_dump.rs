@@ -0,0 +1,76 @@
+use eframe::egui::{self, RichText};
+
+/// The number of bytes displayed on each row of the hex dump.
+const BYTES_PER_ROW: usize = 16;
+
+/// The maximum height of the hex dump, the rows are scrolled past it.
+const MAX_HEIGHT: f32 = 320.0;
+
+/// Displays the bytes in rows of 16: the offset, the bytes in hex and their ASCII characters.
+///
+/// Clicking a byte highlights it in both the hex and the ASCII columns,
+/// the highlighted offset is kept in the egui memory of this widget.
+pub fn hex_dump_ui(ui: &mut egui::Ui, bytes: &[u8]) {
+    let id = ui.id().with("hex_dump");
+    let mut highlighted = ui.data(|d| d.get_temp::<usize>(id));
+    let row_height =
+        ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+    let rows = bytes.len().div_ceil(BYTES_PER_ROW);
+
+    egui::ScrollArea::vertical().id_source(id).max_height(MAX_HEIGHT).show_rows(
+        ui,
+        row_height,
+        rows,
+        |ui, row_range| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            for row in row_range {
+                let offset = row * BYTES_PER_ROW;
+                let line = &bytes[offset..bytes.len().min(offset + BYTES_PER_ROW)];
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{offset:04x}")).monospace().weak());
+                    ui.add_space(8.0);
+                    for column in 0..BYTES_PER_ROW {
+                        if column == BYTES_PER_ROW / 2 {
+                            ui.add_space(8.0);
+                        }
+                        match line.get(column) {
+                            Some(byte) => {
+                                let text = RichText::new(format!("{byte:02x}")).monospace();
+                                byte_ui(ui, text, offset + column, &mut highlighted);
+                            }
+                            // Keeps the ASCII column aligned on the last row.
+                            None => {
+                                ui.label(RichText::new("  ").monospace());
+                            }
+                        }
+                    }
+                    ui.add_space(8.0);
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    for (column, &byte) in line.iter().enumerate() {
+                        let c = if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        };
+                        let text = RichText::new(c.to_string()).monospace();
+                        byte_ui(ui, text, offset + column, &mut highlighted);
+                    }
+                });
+            }
+        },
+    );
+
+    ui.data_mut(|d| match highlighted {
+        Some(offset) => d.insert_temp(id, offset),
+        None => d.remove::<usize>(id),
+    });
+}
+
+/// Displays a byte that is highlighted when its offset is the selected one, clicking toggles it.
+fn byte_ui(ui: &mut egui::Ui, text: RichText, offset: usize, highlighted: &mut Option<usize>) {
+    let selected = *highlighted == Some(offset);
+    let response = ui.selectable_label(selected, text).on_hover_text(format!("Offset {offset}"));
+    if response.clicked() {
+        *highlighted = if selected { None } else { Some(offset) };
+    }
+}
@@ -0,0 +1,179 @@
+use heed::types::Bytes;
+use heed::{Database, RwTxn};
+
+use crate::txn::DirtyRwTxn;
+
+/// A mutation done in the current write transaction, with what is needed to revert it.
+pub enum HistoryEntry {
+    Put {
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        key: Vec<u8>,
+        /// The value replaced by the put, none when the key was inserted.
+        old_value: Option<Vec<u8>>,
+        new_value: Vec<u8>,
+    },
+    Delete {
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        key: Vec<u8>,
+        old_value: Vec<u8>,
+    },
+}
+
+impl HistoryEntry {
+    pub fn database_name(&self) -> &str {
+        let (HistoryEntry::Put { database_name, .. } | HistoryEntry::Delete { database_name, .. }) =
+            self;
+        database_name.as_deref().unwrap_or("{main}")
+    }
+
+    pub fn key(&self) -> &[u8] {
+        let (HistoryEntry::Put { key, .. } | HistoryEntry::Delete { key, .. }) = self;
+        key
+    }
+
+    /// Writes the mutation again, after it was undone.
+    pub fn redo(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        match self {
+            HistoryEntry::Put { database, key, new_value, .. } => {
+                database.put(wtxn, key, new_value)
+            }
+            HistoryEntry::Delete { database, key, .. } => database.delete(wtxn, key).map(drop),
+        }
+    }
+
+    /// Writes back the state the key was in before this mutation.
+    pub fn undo(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        match self {
+            HistoryEntry::Put { database, key, old_value: Some(old_value), .. }
+            | HistoryEntry::Delete { database, key, old_value, .. } => {
+                database.put(wtxn, key, old_value)
+            }
+            HistoryEntry::Put { database, key, old_value: None, .. } => {
+                database.delete(wtxn, key).map(drop)
+            }
+        }
+    }
+}
+
+/// The mutations of the current write transaction, they can be undone and redone.
+#[derive(Default)]
+pub struct History {
+    done: Vec<HistoryEntry>,
+    undone: Vec<HistoryEntry>,
+    /// The number of mutations done by the bulk operations, imports and scripts.
+    untracked: usize,
+}
+
+impl History {
+    /// The mutations in the order they were done.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.done
+    }
+
+    /// The undone mutations, the next one to redo last.
+    pub fn undone(&self) -> &[HistoryEntry] {
+        &self.undone
+    }
+
+    /// Puts the entry into the database and records the value it replaces.
+    pub fn put(
+        &mut self,
+        wtxn: &mut DirtyRwTxn,
+        database_name: &Option<String>,
+        database: Database<Bytes, Bytes>,
+        key: &[u8],
+        value: &[u8],
+    ) -> heed::Result<()> {
+        let old_value = database.get(wtxn, key)?.map(<[u8]>::to_vec);
+        database.put(wtxn, key, value)?;
+        wtxn.mark_dirty();
+        self.record(HistoryEntry::Put {
+            database_name: database_name.clone(),
+            database,
+            key: key.to_vec(),
+            old_value,
+            new_value: value.to_vec(),
+        });
+        Ok(())
+    }
+
+    /// Deletes the entry from the database and records its value, returns whether it existed.
+    pub fn delete(
+        &mut self,
+        wtxn: &mut DirtyRwTxn,
+        database_name: &Option<String>,
+        database: Database<Bytes, Bytes>,
+        key: &[u8],
+    ) -> heed::Result<bool> {
+        let Some(old_value) = database.get(wtxn, key)?.map(<[u8]>::to_vec) else {
+            return Ok(false);
+        };
+        database.delete(wtxn, key)?;
+        wtxn.mark_dirty();
+        self.record(HistoryEntry::Delete {
+            database_name: database_name.clone(),
+            database,
+            key: key.to_vec(),
+            old_value,
+        });
+        Ok(true)
+    }
+
+    /// Puts the entry into the destination and deletes it from the source database,
+    /// returns whether it existed.
+    pub fn move_entry(
+        &mut self,
+        wtxn: &mut DirtyRwTxn,
+        (source_name, source): (&Option<String>, Database<Bytes, Bytes>),
+        (destination_name, destination): (&Option<String>, Database<Bytes, Bytes>),
+        key: &[u8],
+    ) -> heed::Result<bool> {
+        let Some(value) = source.get(wtxn, key)?.map(<[u8]>::to_vec) else {
+            return Ok(false);
+        };
+        self.put(wtxn, destination_name, destination, key, &value)?;
+        self.delete(wtxn, source_name, source, key)
+    }
+
+    /// A new mutation makes the undone ones impossible to redo.
+    fn record(&mut self, entry: HistoryEntry) {
+        self.done.push(entry);
+        self.undone.clear();
+    }
+
+    /// Reverts the last mutation, returns false when there is none.
+    pub fn undo(&mut self, wtxn: &mut DirtyRwTxn) -> heed::Result<bool> {
+        let Some(entry) = self.done.pop() else { return Ok(false) };
+        entry.undo(wtxn)?;
+        wtxn.mark_dirty();
+        self.undone.push(entry);
+        Ok(true)
+    }
+
+    /// Applies the last undone mutation again, returns false when there is none.
+    pub fn redo(&mut self, wtxn: &mut DirtyRwTxn) -> heed::Result<bool> {
+        let Some(entry) = self.undone.pop() else { return Ok(false) };
+        entry.redo(wtxn)?;
+        wtxn.mark_dirty();
+        self.done.push(entry);
+        Ok(true)
+    }
+
+    /// Counts mutations that cannot be undone in the pending ones.
+    pub fn record_untracked(&mut self, count: usize) {
+        self.untracked += count;
+    }
+
+    /// The number of mutations that would be lost by aborting the write transaction.
+    pub fn pending_mutations(&self) -> usize {
+        self.done.len() + self.untracked
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+        self.untracked = 0;
+    }
+}
@@ -0,0 +1,222 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use heed::types::Bytes;
+use heed::{Database, RwTxn};
+
+use crate::export::{FileFormat, JsonEntry, NdjsonEntry};
+
+/// Reads the entries written by the export of the same format and puts them into the database.
+pub fn import(
+    format: FileFormat,
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    match format {
+        FileFormat::Json => import_json(database, wtxn, path),
+        FileFormat::Csv => import_csv(database, wtxn, path),
+        FileFormat::Yaml => import_yaml(database, wtxn, path),
+        FileFormat::Ndjson => import_ndjson(database, wtxn, path),
+    }
+}
+
+/// Reads a JSON array of entries, as written by the JSON export.
+fn import_json(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    let reader = BufReader::new(File::open(path)?);
+    let entries: Vec<JsonEntry> =
+        serde_json::from_reader(reader).map_err(|e| anyhow::anyhow!("line {}: {e}", e.line()))?;
+
+    let count = entries.len();
+    for JsonEntry { key, value } in entries {
+        database
+            .put(wtxn, &key.into_bytes(), &value.into_bytes())
+            .context("while putting the imported entries")?;
+    }
+
+    Ok(count)
+}
+
+/// Reads a JSON object per line, as written by the NDJSON export, the empty lines are ignored.
+fn import_ndjson(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut count = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let NdjsonEntry { key, value } =
+            serde_json::from_str(&line).with_context(|| format!("line {}", i + 1))?;
+        database
+            .put(wtxn, &key.into_bytes(), &value.into_bytes())
+            .context("while putting the imported entries")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Reads the `key,value` rows of a CSV file, fields are either STFU-8 or plain UTF-8.
+fn import_csv(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    let mut reader = csv::ReaderBuilder::new().has_headers(true).from_path(path)?;
+
+    let mut count = 0;
+    for result in reader.records() {
+        let record = result?;
+        let line = record.position().map_or(0, |p| p.line());
+        let (Some(key), Some(value), None) = (record.get(0), record.get(1), record.get(2)) else {
+            anyhow::bail!("line {line}: expected two columns, found {}", record.len());
+        };
+        database
+            .put(wtxn, &csv_field_bytes(key), &csv_field_bytes(value))
+            .context("while putting the imported entries")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Reads a YAML mapping of STFU-8 keys to STFU-8 or `!!binary` values, as written by the YAML export.
+fn import_yaml(
+    database: Database<Bytes, Bytes>,
+    wtxn: &mut RwTxn,
+    path: &Path,
+) -> anyhow::Result<usize> {
+    // serde_yaml resolves `!!binary` to a plain string, redefining the `!!` handle as a local
+    // one keeps the tag. A directive must come before the explicit start of the document,
+    // which can be preceded by comments and blank lines.
+    let text = std::fs::read_to_string(path)?;
+    let first_line =
+        text.lines().map(str::trim_start).find(|l| !l.is_empty() && !l.starts_with('#'));
+    let directive = match first_line {
+        Some(line) if line.starts_with("---") || line.starts_with('%') => "%TAG !! !\n",
+        _ => "%TAG !! !\n---\n",
+    };
+    let mapping = match serde_yaml::from_str(&format!("{directive}{text}"))? {
+        serde_yaml::Value::Mapping(mapping) => mapping,
+        serde_yaml::Value::Null => return Ok(0),
+        _ => anyhow::bail!("expected a mapping of keys to values"),
+    };
+
+    let count = mapping.len();
+    for (key, value) in mapping {
+        let Some(key) = yaml_scalar_text(&key) else {
+            anyhow::bail!("expected a string key, found {key:?}");
+        };
+        let key_bytes = stfu8::decode_u8(&key).with_context(|| format!("invalid key {key:?}"))?;
+        let value = yaml_value_bytes(value).with_context(|| format!("key {key:?}"))?;
+        database.put(wtxn, &key_bytes, &value).context("while putting the imported entries")?;
+    }
+
+    Ok(count)
+}
+
+/// Returns the text of a string, or of a number or boolean that YAML did not keep as a string.
+fn yaml_scalar_text(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Decodes a STFU-8 string, number or boolean, or a `!!binary` base64 string.
+fn yaml_value_bytes(value: serde_yaml::Value) -> anyhow::Result<Vec<u8>> {
+    if let Some(text) = yaml_scalar_text(&value) {
+        return Ok(stfu8::decode_u8(&text)?);
+    }
+    match value {
+        serde_yaml::Value::Tagged(tagged) if tagged.tag == "binary" => match tagged.value {
+            serde_yaml::Value::String(s) => {
+                // Long base64 strings are folded on multiple lines.
+                let base64: String = s.split_whitespace().collect();
+                Ok(BASE64_STANDARD.decode(base64)?)
+            }
+            other => anyhow::bail!("expected a base64 string, found {other:?}"),
+        },
+        other => anyhow::bail!("expected a string or a !!binary value, found {other:?}"),
+    }
+}
+
+/// Plain UTF-8 fields without a backslash decode to themselves,
+/// fields that are not valid STFU-8 are taken as-is.
+pub(crate) fn csv_field_bytes(field: &str) -> Vec<u8> {
+    stfu8::decode_u8(field).unwrap_or_else(|_| field.as_bytes().to_vec())
+}
+
+/// The number of lines inserted in a single frame by a batch insert.
+pub const BATCH_CHUNK_SIZE: usize = 1000;
+
+/// An insertion of the `key\tvalue` lines of a file, where keys and values are STFU-8,
+/// done by chunks in the current write transaction.
+pub struct BatchInsert {
+    pub path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+    pub inserted: usize,
+    /// The number of malformed lines that were not inserted.
+    pub skipped: usize,
+}
+
+impl BatchInsert {
+    pub fn open(path: PathBuf) -> io::Result<BatchInsert> {
+        let lines = BufReader::new(File::open(&path)?).lines();
+        Ok(BatchInsert { path, lines, line_number: 0, inserted: 0, skipped: 0 })
+    }
+
+    /// Inserts the next lines and returns whether the whole file was read,
+    /// the malformed lines are skipped and described in `errors`.
+    pub fn insert_chunk(
+        &mut self,
+        database: Database<Bytes, Bytes>,
+        wtxn: &mut RwTxn,
+        errors: &mut Vec<String>,
+    ) -> anyhow::Result<bool> {
+        for _ in 0..BATCH_CHUNK_SIZE {
+            let Some(line) = self.lines.next() else { return Ok(true) };
+            let line = line?;
+            self.line_number += 1;
+            if line.is_empty() {
+                continue;
+            }
+
+            match parse_batch_line(&line) {
+                Ok((key, value)) => {
+                    database.put(wtxn, &key, &value).context("while putting the entries")?;
+                    self.inserted += 1;
+                }
+                Err(e) => {
+                    errors.push(format!("{}:{}: {e}", self.path.display(), self.line_number));
+                    self.skipped += 1;
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+fn parse_batch_line(line: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let (key, value) = line.split_once('\t').ok_or("missing the tab between the key and value")?;
+    let key = stfu8::decode_u8(key).map_err(|e| format!("invalid key: {e}"))?;
+    let value = stfu8::decode_u8(value).map_err(|e| format!("invalid value: {e}"))?;
+    Ok((key, value))
+}
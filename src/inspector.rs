@@ -0,0 +1,55 @@
+use eframe::egui::{self, Color32};
+
+use crate::format::{decode_json, decode_msgpack};
+use crate::hex_dump::hex_dump_ui;
+
+/// The state of the window showing a single value in every format at once.
+pub struct Inspector {
+    /// The escaped key of the entry, displayed in the title.
+    pub key: String,
+    pub value: Vec<u8>,
+}
+
+impl Inspector {
+    /// Displays the value as stfu8, a hex dump, lossy UTF-8 and the structured formats it decodes as.
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        ui.label(format!("Key: {}", self.key));
+        ui.label(format!("{} bytes", self.value.len()));
+        ui.separator();
+
+        egui::CollapsingHeader::new("stfu8").default_open(true).show(ui, |ui| {
+            read_only_text(ui, stfu8::encode_u8_pretty(&self.value));
+        });
+        egui::CollapsingHeader::new("Hex dump").default_open(true).show(ui, |ui| {
+            hex_dump_ui(ui, &self.value);
+        });
+        egui::CollapsingHeader::new("UTF-8 (lossy)").show(ui, |ui| {
+            read_only_text(ui, String::from_utf8_lossy(&self.value).into_owned());
+        });
+
+        let structured =
+            [("JSON", decode_json(&self.value)), ("MessagePack", decode_msgpack(&self.value))];
+        for (format, decoded) in structured {
+            match decoded {
+                Ok(value) => {
+                    egui::CollapsingHeader::new(format).default_open(true).show(ui, |ui| {
+                        read_only_text(ui, value.pretty);
+                    });
+                }
+                Err(e) => {
+                    ui.colored_label(Color32::GRAY, format!("Not {format}")).on_hover_text(e);
+                }
+            }
+        }
+    }
+}
+
+/// A `&mut &str` makes the text edit read-only, the text can still be selected and copied.
+fn read_only_text(ui: &mut egui::Ui, text: String) {
+    ui.add(
+        egui::TextEdit::multiline(&mut text.as_str())
+            .code_editor()
+            .desired_rows(1)
+            .desired_width(f32::INFINITY),
+    );
+}
@@ -0,0 +1,108 @@
+use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use heed::types::Bytes;
+use heed::{Database, Env, MdbError, RoTxn};
+
+/// The outcome of the iteration over every database of an environment.
+pub struct IntegrityReport {
+    pub databases: Vec<DatabaseCheck>,
+    /// The number of keys of the main database that are entries and not named databases.
+    pub plain_keys: usize,
+}
+
+/// The outcome of the iteration over the entries of a single database.
+pub struct DatabaseCheck {
+    pub name: Option<String>,
+    pub entries: usize,
+    pub error: Option<String>,
+}
+
+impl IntegrityReport {
+    /// Iterates over the entries of the main database and of every database it names,
+    /// the iterated entries are counted in `progress`.
+    pub fn check(env: &Env, rtxn: &RoTxn, progress: &AtomicUsize) -> heed::Result<IntegrityReport> {
+        let mut report = IntegrityReport { databases: Vec::new(), plain_keys: 0 };
+        let Some(main_db) = env.open_database::<Bytes, Bytes>(rtxn, None)? else {
+            return Ok(report);
+        };
+        report.databases.push(DatabaseCheck::check(None, main_db, rtxn, progress));
+
+        for result in main_db.iter(rtxn)? {
+            // The error is already reported by the check of the main database.
+            let Ok((key, _)) = result else { break };
+            let Ok(name) = str::from_utf8(key) else {
+                report.plain_keys += 1;
+                continue;
+            };
+
+            let error = match env.open_database::<Bytes, Bytes>(rtxn, Some(name)) {
+                Ok(Some(database)) => {
+                    let name = Some(name.to_owned());
+                    let check = DatabaseCheck::check(name, database, rtxn, progress);
+                    report.databases.push(check);
+                    continue;
+                }
+                // The key is not the record of a named database but a regular entry.
+                Err(heed::Error::Mdb(MdbError::Incompatible)) => {
+                    report.plain_keys += 1;
+                    continue;
+                }
+                Ok(None) => "listed in the main database but cannot be opened".to_owned(),
+                Err(e) => e.to_string(),
+            };
+            report.databases.push(DatabaseCheck {
+                name: Some(name.to_owned()),
+                entries: 0,
+                error: Some(error),
+            });
+        }
+
+        Ok(report)
+    }
+
+    pub fn errors(&self) -> usize {
+        self.databases.iter().filter(|check| check.error.is_some()).count()
+    }
+}
+
+impl DatabaseCheck {
+    /// Iterates over all the entries and compares their number with the database statistics.
+    fn check(
+        name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        rtxn: &RoTxn,
+        progress: &AtomicUsize,
+    ) -> DatabaseCheck {
+        let mut check = DatabaseCheck { name, entries: 0, error: None };
+        let iter = match database.iter(rtxn) {
+            Ok(iter) => iter,
+            Err(e) => {
+                check.error = Some(e.to_string());
+                return check;
+            }
+        };
+
+        for result in iter {
+            if let Err(e) = result {
+                check.error = Some(format!("after {} entries: {e}", check.entries));
+                return check;
+            }
+            check.entries += 1;
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+
+        match database.len(rtxn) {
+            Ok(len) if len as usize != check.entries => {
+                check.error = Some(format!(
+                    "{} entries were iterated but the statistics count {len}",
+                    check.entries
+                ));
+            }
+            Ok(_) => (),
+            Err(e) => check.error = Some(e.to_string()),
+        }
+
+        check
+    }
+}
@@ -0,0 +1,76 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+
+/// An entry with its size, ordered by size first.
+pub type SizedEntry = (usize, Vec<u8>, Vec<u8>);
+
+/// The part of the entries whose length is ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    KeyLength,
+    ValueLength,
+}
+
+impl Measure {
+    /// The title of the window listing the largest entries.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Measure::KeyLength => "Longest keys",
+            Measure::ValueLength => "Largest values",
+        }
+    }
+
+    fn of(&self, key: &[u8], value: &[u8]) -> usize {
+        match self {
+            Measure::KeyLength => key.len(),
+            Measure::ValueLength => value.len(),
+        }
+    }
+}
+
+/// The state of the window showing the largest entries of a database.
+pub struct Largest {
+    pub measure: Measure,
+    /// The number of entries to keep.
+    pub k: usize,
+    pub entries: heed::Result<Vec<SizedEntry>>,
+}
+
+impl Largest {
+    pub fn new(
+        database: Database<Bytes, Bytes>,
+        rtxn: &RoTxn,
+        measure: Measure,
+        k: usize,
+    ) -> Largest {
+        Largest { measure, k, entries: largest(database, rtxn, measure, k) }
+    }
+}
+
+/// Returns the `k` entries with the largest keys or values, the largest first.
+pub fn largest(
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    measure: Measure,
+    k: usize,
+) -> heed::Result<Vec<SizedEntry>> {
+    // A min-heap of the largest entries seen so far, its top is the one to evict.
+    let mut heap = BinaryHeap::<Reverse<SizedEntry>>::with_capacity(k + 1);
+    for result in database.iter(rtxn)? {
+        let (key, value) = result?;
+        let size = measure.of(key, value);
+        let smallest = heap.peek().map(|Reverse((size, ..))| *size);
+        if heap.len() < k || smallest.is_some_and(|smallest| size > smallest) {
+            heap.push(Reverse((size, key.to_vec(), value.to_vec())));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+    }
+
+    // Sorting the reversed entries in ascending order puts the largest first.
+    Ok(heap.into_sorted_vec().into_iter().map(|Reverse(entry)| entry).collect())
+}
@@ -1,363 +1,1502 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+use std::collections::VecDeque;
 use std::mem;
-use std::ops::Deref;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use eframe::egui::{self, Align, InnerResponse};
+use anyhow::Context;
+use eframe::egui::{self, InnerResponse};
 use egui::Color32;
 use egui_extras::{Column, TableBuilder};
-use egui_tiles::{Container, Tile};
-use heed::types::{Bytes, DecodeIgnore};
-use heed::{Database, Env, EnvOpenOptions, RwTxn};
+use egui_plot::{Bar, BarChart, Plot};
+use egui_tiles::TileId;
+use heed::types::Bytes;
+use heed::{CompactionOption, Database, Env, EnvFlags, RoTxn};
 use txn::Txn;
 
-use crate::escaped_entry::EscapedEntry;
+use crate::database_entries::DatabaseEntries;
+use crate::diff::{DatabaseDiff, DiffKind};
+use crate::display_mode::DisplayMode;
+use crate::errors::{errors_ui, AppError, ReportError};
+use crate::find_replace::FindReplace;
+use crate::history::{History, HistoryEntry};
+use crate::raw_cursor::RawCursor;
+use crate::scripting::ScriptLanguage;
+use crate::settings::{Bookmarks, EnvOptions, Settings};
+use crate::snapshots::Snapshots;
+use crate::stats::DatabaseStats;
+use crate::task::BackgroundTask;
+use crate::toasts::Toasts;
+use crate::workspace::{database_entries_pane, Workspace, REDO_SHORTCUT, UNDO_SHORTCUT};
 
+mod byte_size;
+mod cli;
+mod database_entries;
+mod diff;
+mod display_mode;
+mod errors;
 mod escaped_entry;
+mod export;
+mod find_replace;
+mod format;
+mod hex_dump;
+mod history;
+mod import;
+mod inspector;
+mod integrity;
+mod largest;
+mod prefix_groups;
+mod raw_cursor;
+mod sample;
+mod scripting;
+mod settings;
+mod snapshots;
+mod sqlite;
+mod stats;
+mod task;
+mod theme;
+mod toasts;
 mod txn;
-
-static ENV: OnceLock<Env> = OnceLock::new();
+mod workspace;
 
 fn main() -> anyhow::Result<()> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let args = Args::parse()?;
+    if args.no_gui {
+        let path = args.env_path.context("--no-gui requires the path of an environment")?;
+        let env = workspace::open_env(&path, args.read_only, &EnvOptions::default())?;
+        return cli::run(env, args.read_only);
+    }
+
     let options = eframe::NativeOptions {
         // initial_window_size: Some(egui::vec2(720.0, 480.0)),
         ..Default::default()
     };
 
-    let env_path = std::env::args().nth(1).expect("Expected an environment path as an argument");
-    let env = unsafe { EnvOpenOptions::new().max_dbs(1000).open(env_path)? };
-    let _ = ENV.set(env);
+    // The environment is opened once the settings are loaded, with its saved options,
+    // but a wrong path must fail before the window is shown, e.g. in a CI script.
+    if let Some(path) = &args.env_path {
+        if !path.exists() {
+            anyhow::bail!("{} does not exist", path.display());
+        }
+        if !path.is_dir() {
+            anyhow::bail!("{} is not an LMDB environment directory", path.display());
+        }
+    }
 
-    eframe::run_native("LMDB Editor", options, Box::new(|ctx| Box::new(LmdbEditor::new(ctx))))
-        .unwrap();
+    eframe::run_native(
+        "LMDB Editor",
+        options,
+        Box::new(move |ctx| Box::new(LmdbEditor::new(ctx, args))),
+    )
+    .unwrap();
 
     Ok(())
 }
 
+/// The command line arguments.
+struct Args {
+    /// The environment to open, the start window is shown when there is none.
+    env_path: Option<PathBuf>,
+    /// Opens the environment with `MDB_RDONLY` and disables every write operation.
+    read_only: bool,
+    /// Reads commands from stdin instead of showing a window.
+    no_gui: bool,
+    /// Opens a tab on the unnamed main database even when the environment has named ones.
+    open_main_db: bool,
+}
+
+/// The usage displayed when the command line cannot be parsed.
+const USAGE: &str = "usage: lmdb-editor [--read-only] [--no-gui] [--open-main-db] [ENV_PATH]";
+
+impl Args {
+    /// Parses the command line, the unknown flags are rejected instead of taken as a path.
+    fn parse() -> anyhow::Result<Args> {
+        let mut env_path = None;
+        let mut read_only = false;
+        let mut no_gui = false;
+        let mut open_main_db = false;
+        for arg in std::env::args_os().skip(1) {
+            match arg.to_str() {
+                Some("--read-only") => read_only = true,
+                Some("--no-gui") => no_gui = true,
+                Some("--open-main-db") => open_main_db = true,
+                Some(flag) if flag.starts_with("--") => {
+                    anyhow::bail!("unknown argument {flag}\n{USAGE}")
+                }
+                _ => env_path = Some(PathBuf::from(arg)),
+            }
+        }
+
+        Ok(Args { env_path, read_only, no_gui, open_main_db })
+    }
+}
+
+/// Formats the number with commas between the groups of three digits, e.g. `1,234`.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut output = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i != 0 && (digits.len() - i).is_multiple_of(3) {
+            output.push(',');
+        }
+        output.push(digit);
+    }
+    output
+}
+
+/// Displays the number of entries by length range as a bar chart.
+fn length_histogram_ui(ui: &mut egui::Ui, id: &str, histogram: &[usize]) {
+    let bars = histogram
+        .iter()
+        .enumerate()
+        .map(|(bucket, &count)| {
+            Bar::new(bucket as f64, count as f64).name(stats::histogram_label(bucket))
+        })
+        .collect();
+
+    Plot::new(id)
+        .height(150.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show_grid([false, true])
+        .x_axis_formatter(|mark, _, _| {
+            // The marks between the bars have no label.
+            let bucket = mark.value.round();
+            if (mark.value - bucket).abs() < f64::EPSILON && bucket >= 0.0 {
+                stats::histogram_label(bucket as usize)
+            } else {
+                String::new()
+            }
+        })
+        .show(ui, |plot_ui| plot_ui.bar_chart(BarChart::new(bars).width(0.8)));
+}
+
+/// Displays a mutation of the transaction log, collapsed to its key.
+fn history_entry_ui(ui: &mut egui::Ui, index: usize, entry: &HistoryEntry) {
+    let (operation, color) = match entry {
+        HistoryEntry::Put { old_value: None, .. } => ("insert", Color32::GREEN),
+        HistoryEntry::Put { old_value: Some(_), .. } => ("update", Color32::YELLOW),
+        HistoryEntry::Delete { .. } => ("delete", Color32::RED),
+    };
+    let title = egui::RichText::new(format!(
+        "#{} {operation} {} in {}",
+        index + 1,
+        stfu8::encode_u8_pretty(entry.key()),
+        entry.database_name()
+    ))
+    .color(color);
+
+    egui::CollapsingHeader::new(title).id_source(index).show(ui, |ui| {
+        let (old_value, new_value) = match entry {
+            HistoryEntry::Put { old_value, new_value, .. } => {
+                (old_value.as_deref(), Some(&new_value[..]))
+            }
+            HistoryEntry::Delete { old_value, .. } => (Some(&old_value[..]), None),
+        };
+        egui::Grid::new(("history_entry", index)).num_columns(2).show(ui, |ui| {
+            ui.label("Old value");
+            ui.label(old_value.map_or_else(|| "none".to_owned(), stfu8::encode_u8_pretty));
+            ui.end_row();
+            ui.label("New value");
+            ui.label(new_value.map_or_else(|| "none".to_owned(), stfu8::encode_u8_pretty));
+            ui.end_row();
+        });
+    });
+}
+
+/// How the tile tree is simplified after the panes are dragged around.
+fn simplification_options() -> egui_tiles::SimplificationOptions {
+    // A pane dropped next to another keeps its tab, it can be dragged again.
+    egui_tiles::SimplificationOptions { all_panes_must_have_tabs: true, ..Default::default() }
+}
+
+/// The tooltip displayed on the disabled write operations.
+const READ_ONLY_HINT: &str = "The environment is opened in read-only mode or the writes are locked";
+
+/// The number of successive values kept by a key watch tab.
+const MAX_WATCHED_VALUES: usize = 1000;
+
+/// The default number of seconds between two reads of a watched key.
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 2;
+
 struct LmdbEditor {
-    txn: txn::Txn,
-    tree: egui_tiles::Tree<Pane>,
+    /// The opened environments, each one with its own transaction and tabs.
+    workspaces: Vec<Workspace>,
+    /// The index of the workspace being displayed.
+    active_workspace: usize,
+    settings: Settings,
+    read_only: bool,
+    /// Whether the unnamed main database is opened even when there are named databases.
+    open_main_db: bool,
+    toasts: Toasts,
+    /// The failed operations not yet dismissed.
+    errors: VecDeque<AppError>,
+    /// The environment about to be opened and the options to open it with.
+    open_dialog: Option<OpenDialog>,
+}
+
+/// The state of the window configuring an environment before opening it.
+struct OpenDialog {
+    path: PathBuf,
+    options: EnvOptions,
+    /// The map size as typed, with an optional unit.
+    map_size: String,
+    /// The size of the data file, `None` when the environment is not created yet.
+    file_size: Option<usize>,
 }
 
 impl LmdbEditor {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // TODO do not try to create the database here.
-        let env = ENV.get().unwrap();
-        let mut wtxn = env.write_txn().unwrap();
-        let main_db = env.create_database(&mut wtxn, None).unwrap();
-        wtxn.commit().unwrap();
-
-        let mut tiles = egui_tiles::Tiles::default();
-        let tabs = vec![
-            tiles.insert_pane(Pane::DatabaseEntries {
-                database_name: None,
-                database: main_db,
-                entry_to_insert: EscapedEntry::default(),
-                jump_to_key: String::new(),
-            }),
-            tiles.insert_pane(Pane::OpenNew { database_to_open: String::new() }),
-        ];
-        let root = tiles.insert_tab_tile(tabs);
-        let tree = egui_tiles::Tree::new("blabla", root, tiles);
-
-        let rtxn = env.read_txn().unwrap();
-        LmdbEditor { txn: txn::Txn::Ro(rtxn), tree }
+    fn new(cc: &eframe::CreationContext<'_>, args: Args) -> Self {
+        let settings = Settings::load(cc.storage);
+        cc.egui_ctx.set_zoom_factor(settings.zoom_factor());
+        cc.egui_ctx.set_visuals(settings.theme.visuals());
+        if let Some(size) = settings.window_size {
+            cc.egui_ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+        }
+        if let Some(position) = settings.window_position {
+            cc.egui_ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position.into()));
+        }
+
+        let mut editor = LmdbEditor {
+            workspaces: Vec::new(),
+            active_workspace: 0,
+            settings,
+            read_only: args.read_only,
+            open_main_db: args.open_main_db,
+            toasts: Toasts::default(),
+            errors: VecDeque::new(),
+            open_dialog: None,
+        };
+        // The environment given on the command line is opened without asking for the options.
+        if let Some(path) = args.env_path {
+            let options = editor.saved_env_options(&path);
+            editor.open_environment(path, args.read_only, options);
+        }
+        editor
+    }
+
+    /// Returns the options the environment was last opened with, or the default ones
+    /// with the map size stored in its data file, if any.
+    fn saved_env_options(&self, path: &Path) -> EnvOptions {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.settings.env_options.get(&canonical).copied().unwrap_or_else(|| {
+            let default = EnvOptions::default();
+            let map_size = workspace::detect_env_map_size(path).unwrap_or(default.map_size);
+            EnvOptions { map_size, ..default }
+        })
+    }
+
+    /// Shows the window configuring the environment, or brings it to the front if it is opened.
+    fn request_open(&mut self, path: PathBuf) {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if let Some(index) = self.workspaces.iter().position(|w| w.env.path() == canonical) {
+            self.active_workspace = index;
+            return;
+        }
+        let options = self.saved_env_options(&path);
+        let map_size = byte_size::format(options.map_size);
+        let file_size =
+            std::fs::metadata(path.join("data.mdb")).ok().map(|metadata| metadata.len() as usize);
+        self.open_dialog = Some(OpenDialog { path, options, map_size, file_size });
+    }
+
+    /// Displays the window configuring the environment to open, opens it on validation.
+    fn open_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.open_dialog else { return };
+        let mut open = true;
+        let mut validated = false;
+        let mut cancelled = false;
+        egui::Window::new("Open an environment")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(dialog.path.display().to_string());
+                let map_size = byte_size::parse(&dialog.map_size);
+                egui::CollapsingHeader::new("Advanced").show(ui, |ui| {
+                    egui::Grid::new("env_options").num_columns(2).show(ui, |ui| {
+                        ui.label("Map size");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut dialog.map_size).desired_width(100.0),
+                        );
+                        if map_size.is_none() {
+                            ui.painter().rect_stroke(
+                                response.rect,
+                                2.0,
+                                egui::Stroke::new(1.0, Color32::RED),
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Max databases");
+                        ui.add(
+                            egui::DragValue::new(&mut dialog.options.max_dbs)
+                                .clamp_range(0..=u32::MAX),
+                        );
+                        ui.end_row();
+                        ui.label("Max readers");
+                        ui.add(
+                            egui::DragValue::new(&mut dialog.options.max_readers)
+                                .clamp_range(1..=u32::MAX),
+                        );
+                        ui.end_row();
+                    });
+                });
+                if let (Some(map_size), Some(file_size)) = (map_size, dialog.file_size) {
+                    if map_size < file_size {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            format!(
+                                "The map size is smaller than the data file ({}), \
+                                LMDB will grow it to the size of the file.",
+                                byte_size::format(file_size)
+                            ),
+                        );
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(map_size.is_some(), egui::Button::new("Open")).clicked() {
+                        dialog.options.map_size = map_size.unwrap();
+                        validated = true;
+                    }
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if validated {
+            let OpenDialog { path, options, .. } = self.open_dialog.take().unwrap();
+            self.open_environment(path, self.read_only, options);
+        } else if !open || cancelled {
+            self.open_dialog = None;
+        }
+    }
+
+    /// Opens the environment at this path in a new workspace and displays it.
+    ///
+    /// An environment that is already opened is only brought to the front,
+    /// two write transactions on the same environment would block each other.
+    fn open_environment(&mut self, path: PathBuf, read_only: bool, options: EnvOptions) {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if let Some(index) = self.workspaces.iter().position(|w| w.env.path() == canonical) {
+            self.active_workspace = index;
+            return;
+        }
+
+        match Workspace::open(&path, read_only, self.open_main_db, &options) {
+            Ok(mut workspace) => {
+                let path = workspace.env.path().to_path_buf();
+                self.settings.env_options.insert(path.clone(), options);
+                if let Some(session) = self.settings.sessions.get(&path) {
+                    workspace.restore_session(session, &mut self.toasts);
+                }
+                workspace.bookmarks =
+                    self.settings.bookmarks.get(&path).cloned().unwrap_or_default();
+                self.settings.add_recent_environment(path);
+                self.workspaces.push(workspace);
+                self.active_workspace = self.workspaces.len() - 1;
+            }
+            Err(e) => self.toasts.add(format!("{e:#}")),
+        }
+    }
+
+    /// Shows the open dialog of the environment folders dropped on the window,
+    /// and a hint while they are dragged over it.
+    fn dropped_environments_ui(&mut self, ctx: &egui::Context) {
+        let (hovering, dropped) =
+            ctx.input(|i| (!i.raw.hovered_files.is_empty(), i.raw.dropped_files.clone()));
+
+        if hovering {
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_hint"));
+            let painter = ctx.layer_painter(layer);
+            let screen = ctx.screen_rect();
+            painter.rect_filled(screen, 0.0, Color32::from_black_alpha(192));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop LMDB folder here",
+                egui::FontId::proportional(24.0),
+                Color32::WHITE,
+            );
+        }
+
+        for path in dropped.into_iter().filter_map(|file| file.path) {
+            let name = path.display().to_string();
+            // Dropping the data file of an environment opens its folder.
+            let folder =
+                if path.is_file() { path.parent().map(Path::to_path_buf) } else { Some(path) };
+            match folder {
+                Some(folder)
+                    if ["data.mdb", "lock.mdb"].iter().any(|f| folder.join(f).exists()) =>
+                {
+                    self.request_open(folder)
+                }
+                _ => self.toasts.add(format!("{name} is not an LMDB environment folder")),
+            }
+        }
+    }
+
+    /// Displays the recently opened environments as buttons and returns the clicked one.
+    fn recent_environments_ui(&mut self, ui: &mut egui::Ui) -> Option<PathBuf> {
+        if self.settings.recent_environments.is_empty() {
+            ui.label("No recent environment");
+        }
+
+        let mut clicked = None;
+        for path in &self.settings.recent_environments {
+            if ui.button(path.display().to_string()).clicked() {
+                clicked = Some(path.clone());
+            }
+        }
+        clicked
     }
 }
 
+/// Displays the progress of the running task, or the status once it is finished.
+fn task_status_ui(
+    ui: &mut egui::Ui,
+    task: &mut Option<BackgroundTask>,
+    status: &mut Option<String>,
+) {
+    if let Some(running) = task {
+        match running.try_finish() {
+            Some(Ok(message)) => *status = Some(message),
+            Some(Err(e)) => *status = Some(format!("{} failed: {e}", running.name)),
+            None => {
+                ui.spinner();
+                ui.label(format!("{}: {} entries", running.name, running.progress()));
+                ui.ctx().request_repaint();
+                return;
+            }
+        }
+        *task = None;
+    }
+
+    if let Some(status) = status {
+        ui.label(status.as_str());
+    }
+}
+
+/// Displays the list of database names as clickable labels and returns the clicked one.
+fn database_list_ui(ui: &mut egui::Ui, database_names: &[String]) -> Option<String> {
+    if database_names.is_empty() {
+        ui.label("No named database found");
+    }
+
+    let mut clicked = None;
+    egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+        for name in database_names {
+            if ui.selectable_label(false, name).clicked() {
+                clicked = Some(name.clone());
+            }
+        }
+    });
+    clicked
+}
+
 impl eframe::App for LmdbEditor {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                let env = ENV.get().unwrap();
-                let button = if matches!(self.txn, Txn::Rw(_)) {
-                    egui::Button::new("currently writing").fill(Color32::GREEN)
-                } else {
-                    egui::Button::new("start writing")
-                };
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            if let Some(rect) = viewport.inner_rect {
+                self.settings.window_size = Some(rect.size().into());
+            }
+            if let Some(rect) = viewport.outer_rect {
+                self.settings.window_position = Some(rect.min.into());
+            }
+        });
 
-                if ui.add(button).clicked() && matches!(self.txn, Txn::Ro(_)) {
-                    let wtxn = env.write_txn().unwrap();
-                    self.txn = txn::Txn::Rw(wtxn);
+        let mut to_open = None;
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                let sidebar = ui
+                    .selectable_label(self.settings.show_sidebar, "☰")
+                    .on_hover_text("Show the list of databases");
+                if sidebar.clicked() {
+                    self.settings.show_sidebar = !self.settings.show_sidebar;
                 }
-
-                if matches!(self.txn, Txn::Rw(_)) {
-                    if ui.button("commit changes").clicked() {
-                        self.txn.commit(env);
+                ui.menu_button("File", |ui| {
+                    if ui.button("Open environment…").clicked() {
+                        ui.close_menu();
+                        to_open = rfd::FileDialog::new().pick_folder();
                     }
+                    ui.menu_button("Recent", |ui| {
+                        if let Some(path) = self.recent_environments_ui(ui) {
+                            ui.close_menu();
+                            to_open = Some(path);
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Remember");
+                            let max = &mut self.settings.max_recent_environments;
+                            if ui.add(egui::DragValue::new(max).clamp_range(1..=50)).changed() {
+                                self.settings.recent_environments.truncate(*max);
+                            }
+                            ui.label("environments");
+                        });
+                        if ui.button("Clear").clicked() {
+                            self.settings.recent_environments.clear();
+                        }
+                    });
+                });
+                ui.menu_button("Settings", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Warn when the map is");
+                        let mut percent = self.settings.map_full_warning * 100.0;
+                        let drag =
+                            egui::DragValue::new(&mut percent).clamp_range(1..=100).suffix("%");
+                        if ui.add(drag).changed() {
+                            self.settings.map_full_warning = percent / 100.0;
+                        }
+                        ui.label("full");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Font size");
+                        let slider = egui::Slider::new(&mut self.settings.font_size, 10.0..=24.0);
+                        // Scaling while dragging would move the slider under the pointer.
+                        let slider = ui.add(slider);
+                        if slider.drag_released() || (slider.changed() && !slider.dragged()) {
+                            ctx.set_zoom_factor(self.settings.zoom_factor());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Truncate the values after");
+                        let drag = egui::DragValue::new(&mut self.settings.max_value_display_bytes)
+                            .clamp_range(16..=65536)
+                            .suffix(" bytes");
+                        ui.add(drag);
+                    });
+                    ui.checkbox(
+                        &mut self.settings.skip_abort_confirmation,
+                        "Abort the uncommitted changes without asking",
+                    );
+                });
 
-                    if ui.button("abort changes").clicked() {
-                        self.txn.abort(env);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let theme = self.settings.theme;
+                    let button = ui
+                        .button(theme.name())
+                        .on_hover_text(format!("Switch to the {} theme", theme.next().name()));
+                    if button.clicked() {
+                        self.settings.theme = theme.next();
+                        ctx.set_visuals(self.settings.theme.visuals());
                     }
-                } else if ui.button("refresh").clicked() {
-                    self.txn.refresh(env);
-                }
+                });
             });
 
-            let LmdbEditor { ref mut txn, tree } = self;
-
-            let mut behavior = TreeBehavior { txn };
-            tree.ui(&mut behavior, ui);
-
-            // Automatically insert an OpenNew Tab when one is missing
-            if let Some(root) = self.tree.root() {
-                let must_insert = match self.tree.tiles.get(root).unwrap() {
-                    Tile::Container(Container::Tabs(tabs)) => {
-                        !tabs.children.iter().any(|&tile_id| {
-                            self.tree.tiles.get(tile_id).map_or(
-                                true,
-                                |tile| matches!(tile, Tile::Pane(pane) if pane.is_open_new()),
-                            )
-                        })
+            let mut to_close = None;
+            ui.horizontal(|ui| {
+                for (i, workspace) in self.workspaces.iter().enumerate() {
+                    let name = workspace.name();
+                    let label = ui.selectable_label(i == self.active_workspace, name);
+                    if label.on_hover_text(workspace.env.path().display().to_string()).clicked() {
+                        self.active_workspace = i;
                     }
-                    _ => false,
-                };
-
-                if must_insert {
-                    let tid = self
-                        .tree
-                        .tiles
-                        .insert_pane(Pane::OpenNew { database_to_open: String::new() });
-                    if let Tile::Container(Container::Tabs(t)) =
-                        self.tree.tiles.get_mut(root).unwrap()
-                    {
-                        t.children.push(tid);
+                    if ui.small_button("x").on_hover_text("Close this environment").clicked() {
+                        to_close = Some(i);
                     }
+                    ui.separator();
+                }
+            });
+
+            if let Some(i) = to_close {
+                self.workspaces.remove(i);
+                if self.active_workspace >= i && self.active_workspace > 0 {
+                    self.active_workspace -= 1;
                 }
             }
+
+            errors_ui(ui, &mut self.errors);
         });
+
+        match self.workspaces.get_mut(self.active_workspace) {
+            Some(workspace) => {
+                workspace.ui(
+                    ctx,
+                    &mut self.settings,
+                    self.read_only,
+                    &mut self.toasts,
+                    &mut self.errors,
+                );
+                // The snapshots are restored read-only, they must stay as they were taken.
+                if let Some(path) = workspace.snapshot_to_open.take() {
+                    let options = self.saved_env_options(&path);
+                    self.open_environment(path, true, options);
+                }
+            }
+            None => {
+                egui::CentralPanel::default().show(ctx, |_ui| ());
+                egui::Window::new("Open an LMDB environment")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        if ui.button("Browse…").clicked() {
+                            to_open = rfd::FileDialog::new().pick_folder();
+                        }
+                        ui.separator();
+                        ui.label("Recent environments");
+                        if let Some(path) = self.recent_environments_ui(ui) {
+                            to_open = Some(path);
+                        }
+                    });
+            }
+        }
+
+        if let Some(path) = to_open {
+            self.request_open(path);
+        }
+        self.open_dialog_ui(ctx);
+
+        self.dropped_environments_ui(ctx);
+
+        self.toasts.ui(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        for workspace in &self.workspaces {
+            let path = workspace.env.path().to_path_buf();
+            self.settings.sessions.insert(path.clone(), workspace.session());
+            self.settings.bookmarks.insert(path, workspace.bookmarks.clone());
+        }
+        self.settings.save(storage);
     }
 }
 
 enum Pane {
-    DatabaseEntries {
+    DatabaseEntries(Box<DatabaseEntries>),
+    OpenNew {
+        database_to_open: String,
+    },
+    DatabaseList,
+    DatabaseStats {
         database_name: Option<String>,
         database: Database<Bytes, Bytes>,
-        entry_to_insert: EscapedEntry,
-        jump_to_key: String,
+        stats: Option<DatabaseStats>,
     },
-    OpenNew {
-        database_to_open: String,
+    EnvInfo {
+        /// The copy of the environment without its free pages, when running.
+        compaction: Option<BackgroundTask>,
+        /// The copy of the environment as-is and its destination, when running.
+        backup: Option<(BackgroundTask, PathBuf)>,
+    },
+    Diff {
+        left: Database<Bytes, Bytes>,
+        left_name: Option<String>,
+        right: Database<Bytes, Bytes>,
+        right_name: Option<String>,
+        diff: Option<DatabaseDiff>,
+    },
+    TransactionLog,
+    Bookmarks,
+    KeyWatch {
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        key: Vec<u8>,
+        /// The successive values of the key, the oldest first, `None` when it is missing.
+        history: VecDeque<(Instant, Option<Vec<u8>>)>,
+        /// The number of seconds between two reads of the key.
+        interval_secs: u64,
+        /// When the key was last read, `None` until the first read.
+        last_poll: Option<Instant>,
+    },
+    FindReplace(Box<FindReplace>),
+    RawCursor(Box<RawCursor>),
+    Snapshots(Box<Snapshots>),
+    ScriptConsole {
+        language: ScriptLanguage,
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+        script: String,
+        output: String,
     },
 }
 
 impl Pane {
+    fn database_entries(
+        env: &Env,
+        rtxn: &RoTxn,
+        database_name: Option<String>,
+        database: Database<Bytes, Bytes>,
+    ) -> Pane {
+        let has_dup_sort = database_entries::is_dup_sort(env, rtxn, database_name.as_deref());
+        Pane::DatabaseEntries(Box::new(DatabaseEntries::new(database_name, database, has_dup_sort)))
+    }
+
     fn is_open_new(&self) -> bool {
         matches!(self, Pane::OpenNew { .. })
     }
 }
 
 struct TreeBehavior<'a> {
-    txn: &'a mut txn::Txn,
+    env: &'static Env,
+    txn: &'a mut Txn,
+    /// Changes every time the read transaction is renewed, for the panes caching what they read.
+    txn_generation: u64,
+    /// The mutations done in the current write transaction.
+    history: &'a mut History,
+    /// The pinned keys of the environment.
+    bookmarks: &'a mut Bookmarks,
+    database_names: &'a [String],
+    /// The databases displayed in a tab, by name.
+    open_databases: &'a [(Option<String>, Database<Bytes, Bytes>)],
+    settings: &'a mut Settings,
+    read_only: bool,
+    toasts: &'a mut Toasts,
+    errors: &'a mut VecDeque<AppError>,
+    /// The database tab being renamed and the alias typed so far.
+    renaming_tab: &'a mut Option<(TileId, String)>,
+    /// The alias chosen for a database tab, the panes cannot be modified from their tab.
+    tab_alias: Option<(TileId, String)>,
+    /// A database tab the user asked to display side by side.
+    tile_to_split: Option<TileId>,
+    /// A pane the user asked to open next to the tile it comes from.
+    pane_to_open: Option<(TileId, Pane)>,
+    /// The snapshot folder to open read-only in a new workspace.
+    snapshot_to_open: Option<PathBuf>,
+    /// Whether a pane committed, aborted or refreshed the transaction.
+    txn_changed: bool,
 }
 
 impl egui_tiles::Behavior<Pane> for TreeBehavior<'_> {
     fn tab_title_for_pane(&mut self, pane: &Pane) -> egui::WidgetText {
         match pane {
-            Pane::DatabaseEntries { database_name: Some(name), .. } => name.into(),
-            Pane::DatabaseEntries { database_name: None, .. } => "{main}".into(),
+            Pane::DatabaseEntries(entries) => {
+                let name = entries.title();
+                let Some(count) = entries.entry_count(self.txn, self.txn_generation) else {
+                    return name.into();
+                };
+                // The name takes the color of the tab, the count is dimmer.
+                let mut job = egui::text::LayoutJob::default();
+                let format = |color| egui::TextFormat { color, ..Default::default() };
+                job.append(name, 0.0, format(Color32::PLACEHOLDER));
+                job.append(&format!(" ({})", thousands(count)), 0.0, format(Color32::GRAY));
+                job.into()
+            }
             Pane::OpenNew { .. } => "Open new database".into(),
+            Pane::DatabaseList => "Databases".into(),
+            Pane::DatabaseStats { database_name: Some(name), .. } => format!("{name} stats").into(),
+            Pane::DatabaseStats { database_name: None, .. } => "{main} stats".into(),
+            Pane::EnvInfo { .. } => "Environment".into(),
+            Pane::Diff { left_name, right_name, .. } => format!(
+                "{} ↔ {}",
+                left_name.as_deref().unwrap_or("{main}"),
+                right_name.as_deref().unwrap_or("{main}")
+            )
+            .into(),
+            Pane::TransactionLog => "Transaction log".into(),
+            Pane::Bookmarks => "Bookmarks".into(),
+            Pane::KeyWatch { key, .. } => format!("Watch {}", stfu8::encode_u8_pretty(key)).into(),
+            Pane::FindReplace(_) => "Find and replace".into(),
+            Pane::Snapshots(_) => "Snapshots".into(),
+            Pane::RawCursor(cursor) => {
+                format!("Cursor on {}", cursor.database_name.as_deref().unwrap_or("{main}")).into()
+            }
+            Pane::ScriptConsole { language, database_name, .. } => format!(
+                "{} {} console",
+                database_name.as_deref().unwrap_or("{main}"),
+                language.name()
+            )
+            .into(),
         }
     }
 
+    fn simplification_options(&self) -> egui_tiles::SimplificationOptions {
+        simplification_options()
+    }
+
+    /// Displays the tab like the default implementation, or a text field when it is renamed.
+    fn tab_ui(
+        &mut self,
+        tiles: &egui_tiles::Tiles<Pane>,
+        ui: &mut egui::Ui,
+        id: egui::Id,
+        tile_id: TileId,
+        active: bool,
+        is_being_dragged: bool,
+    ) -> egui::Response {
+        if let Some((_, alias)) = self.renaming_tab.as_mut().filter(|(t, _)| *t == tile_id) {
+            let response = ui
+                .add(egui::TextEdit::singleline(alias).id(id.with("rename")).desired_width(120.0));
+            // Enter and clicking away keep the alias, Escape cancels.
+            if response.lost_focus() {
+                if !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.tab_alias = Some((tile_id, mem::take(alias)));
+                }
+                *self.renaming_tab = None;
+            }
+            return response;
+        }
+
+        let text = self.tab_title_for_tile(tiles, tile_id);
+        let font_id = egui::TextStyle::Button.resolve(ui.style());
+        let galley = text.into_galley(ui, Some(false), f32::INFINITY, font_id);
+        let x_margin = self.tab_title_spacing(ui.visuals());
+        let (_, rect) =
+            ui.allocate_space(egui::vec2(galley.size().x + 2.0 * x_margin, ui.available_height()));
+        let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+
+        // The dragged tab leaves a gap.
+        if ui.is_rect_visible(rect) && !is_being_dragged {
+            let bg_color = self.tab_bg_color(ui.visuals(), tiles, tile_id, active);
+            let stroke = self.tab_outline_stroke(ui.visuals(), tiles, tile_id, active);
+            ui.painter().rect(rect.shrink(0.5), 0.0, bg_color, stroke);
+            if active {
+                // Connects the active tab with its pane.
+                let stroke = egui::Stroke::new(stroke.width + 1.0, bg_color);
+                ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+            }
+            let text_color = self.tab_text_color(ui.visuals(), tiles, tile_id, active);
+            let position = egui::Align2::CENTER_CENTER.align_size_within_rect(galley.size(), rect);
+            ui.painter().galley(position.min, galley, text_color);
+        }
+
+        self.on_tab_button(tiles, tile_id, response)
+    }
+
+    fn on_tab_button(
+        &mut self,
+        tiles: &egui_tiles::Tiles<Pane>,
+        tile_id: TileId,
+        button_response: egui::Response,
+    ) -> egui::Response {
+        if button_response.double_clicked() {
+            if let Some(egui_tiles::Tile::Pane(Pane::DatabaseEntries(entries))) = tiles.get(tile_id)
+            {
+                *self.renaming_tab = Some((tile_id, entries.title().to_owned()));
+                let id = button_response.id.with("rename");
+                button_response.ctx.memory_mut(|m| m.request_focus(id));
+            }
+        }
+        button_response
+    }
+
     fn pane_ui(
         &mut self,
         ui: &mut egui::Ui,
-        _tile_id: egui_tiles::TileId,
+        tile_id: TileId,
         pane: &mut Pane,
     ) -> egui_tiles::UiResponse {
         ui.add_space(5.0);
 
+        // A pane before this one ended the transaction without being able to open a new one.
+        if self.txn.is_none() {
+            return egui_tiles::UiResponse::None;
+        }
+
         match pane {
-            Pane::DatabaseEntries {
-                database,
-                entry_to_insert,
-                database_name,
-                ref mut jump_to_key,
-                ..
-            } => {
-                ui.add(egui::TextEdit::singleline(jump_to_key).hint_text("jump to key"));
-
-                let name = database_name.as_ref().map_or_else(|| "{main}".to_owned(), Clone::clone);
-                egui::Window::new(format!("Put an entry into {name}")).default_pos([720.0, 480.0]).show(ui.ctx(), |ui| {
-                    ui.style_mut().spacing.interact_size.y = 0.0; // hack to make `horizontal_wrapped` work better with text.
-
-                    ui.label("We use STFU-8 as a hacky text encoding/decoding protocol for data that might be not quite UTF-8 but is still mostly UTF-8. \
-                    It is based on the syntax of the repr created when you write (or print) binary text in python, C or other common programming languages.");
-
-                    ui.add_space(8.0);
-
-                    ui.label("Basically STFU-8 is the text format you already write when use escape codes in C, python, rust, etc. \
-                    It permits binary data in UTF-8 by escaping them with \\, for instance \\n and \\x0F.");
-
-                    ui.add_space(8.0);
-
-                    ui.horizontal_wrapped(|ui| {
-                        ui.spacing_mut().item_spacing.x = 0.0;
-                        ui.label("More about how we interpret encoding/decoding ");
-                        ui.hyperlink_to("on the stfu8 documentation", "https://docs.rs/stfu8");
-                        ui.label(".");
-                    });
+            Pane::DatabaseEntries(entries) => entries.ui(self, ui, tile_id),
+            Pane::OpenNew { database_to_open } => {
+                let response = ui.horizontal(|ui| {
+                    let rtxn = self.txn.read_txn();
 
-                    ui.separator();
+                    ui.add(egui::TextEdit::singleline(database_to_open).hint_text("database name"));
+                    if ui.button("open").clicked() {
+                        let env = self.env;
+                        let database_name = if database_to_open.is_empty() {
+                            None
+                        } else {
+                            Some(mem::take(database_to_open))
+                        };
 
-                    let EscapedEntry { key, data } = entry_to_insert;
-                    ui.add(egui::TextEdit::singleline(key).hint_text("escaped key"));
-                    ui.add(egui::TextEdit::multiline(data).hint_text("escaped data"));
+                        env.open_database(rtxn, database_name.as_ref().map(AsRef::as_ref))
+                            .report(self.errors, "Could not open the database")
+                            .flatten()
+                            .map(|database| {
+                                Pane::database_entries(env, rtxn, database_name, database)
+                            })
+                    } else {
+                        None
+                    }
+                });
 
-                    if ui.button("insert").clicked() {
-                        if let txn::Txn::Rw(ref mut wtxn) = self.txn {
-                            let key = entry_to_insert.decoded_key().unwrap();
-                            let data = entry_to_insert.decoded_data().unwrap();
-                            database.put(wtxn, &key, &data).unwrap();
-                            entry_to_insert.clear();
-                        }
+                if let InnerResponse { inner: Some(p), .. } = response {
+                    *pane = p;
+                }
+            }
+            Pane::DatabaseList => {
+                if let Some(name) = database_list_ui(ui, self.database_names) {
+                    let pane = database_entries_pane(self.env, self.txn.read_txn(), name)
+                        .report(self.errors, "Could not open the database");
+                    if let Some(pane) = pane.flatten() {
+                        self.pane_to_open = Some((tile_id, pane));
                     }
+                }
+            }
+            Pane::EnvInfo { compaction, backup } => {
+                let env = self.env;
+                let info = env.info();
+
+                egui::Grid::new("env_info").num_columns(2).striped(true).show(ui, |ui| {
+                    ui.label("Path");
+                    ui.label(env.path().display().to_string());
+                    ui.end_row();
+
+                    ui.label("Map address");
+                    ui.label(format!("{:?}", info.map_addr));
+                    ui.end_row();
+
+                    ui.label("Map size");
+                    ui.label(format!("{} bytes", info.map_size));
+                    ui.end_row();
+
+                    ui.label("File size");
+                    match env.real_disk_size() {
+                        Ok(size) => ui.label(format!("{size} bytes")),
+                        Err(e) => ui.colored_label(Color32::RED, e.to_string()),
+                    };
+                    ui.end_row();
 
-                    if ui.button("delete").clicked() {
-                        if let txn::Txn::Rw(ref mut wtxn) = self.txn {
-                            let key = entry_to_insert.decoded_key().unwrap();
-                            database.delete(wtxn, &key).unwrap();
-                            entry_to_insert.clear();
+                    ui.label("Last page number");
+                    ui.label(info.last_page_number.to_string());
+                    ui.end_row();
+
+                    ui.label("Last transaction ID");
+                    ui.label(info.last_txn_id.to_string());
+                    ui.end_row();
+
+                    ui.label("Max readers");
+                    ui.label(info.maximum_number_of_readers.to_string());
+                    ui.end_row();
+
+                    ui.label("Current readers");
+                    ui.label(info.number_of_readers.to_string());
+                    ui.end_row();
+
+                    ui.label("Max key size");
+                    ui.label(format!("{} bytes", env.max_key_size()));
+                    ui.end_row();
+
+                    ui.label("Flags");
+                    match env.get_flags() {
+                        Ok(flags) => {
+                            let names: Vec<_> = EnvFlags::from_bits_truncate(flags)
+                                .iter_names()
+                                .map(|(name, _)| name)
+                                .collect();
+                            if names.is_empty() {
+                                ui.label("none");
+                            } else {
+                                ui.label(names.join(", "));
+                            }
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, e.to_string());
                         }
                     }
+                    ui.end_row();
                 });
 
-                // If there is a write txn opened, use it, otherwise make the wtxn live longer and deref it.
-                let long_wtxn: &RwTxn;
-                let rtxn = match self.txn {
-                    txn::Txn::Ro(ref rtxn) => rtxn,
-                    txn::Txn::Rw(ref wtxn) => {
-                        long_wtxn = wtxn;
-                        long_wtxn.deref()
+                ui.separator();
+
+                if ui.button("Clear stale readers").clicked() {
+                    match env.clear_stale_readers() {
+                        Ok(count) => self.toasts.add(format!("Cleared {count} stale reader slots")),
+                        Err(e) => {
+                            self.toasts.add(format!("Could not clear the stale readers: {e}"))
+                        }
                     }
-                    txn::Txn::None => unreachable!(),
-                };
+                }
 
-                let scroll_to = if !jump_to_key.is_empty() {
-                    let iter = database.iter(rtxn).unwrap().remap_data_type::<DecodeIgnore>();
-                    let mut count = 0;
-                    for (i, result) in iter.enumerate() {
-                        let (k, _) = result.unwrap();
-                        count = i;
-                        if k >= jump_to_key.as_bytes() {
-                            break;
+                match compaction {
+                    Some(task) => match task.try_finish() {
+                        Some(Ok(message)) => {
+                            self.toasts.add(message);
+                            *compaction = None;
+                        }
+                        Some(Err(e)) => {
+                            self.toasts.add(format!("The compaction failed: {e:#}"));
+                            *compaction = None;
+                        }
+                        None => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Compacting the environment…");
+                            });
+                            ui.ctx().request_repaint();
+                        }
+                    },
+                    None => {
+                        let compact = ui.button("Compact to…").on_hover_text(
+                            "Copies the environment into a new folder without its free pages",
+                        );
+                        if compact.clicked() {
+                            if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                                *compaction =
+                                    Some(BackgroundTask::spawn("Compacting", move |_| {
+                                        // The copy is done in a read transaction, the environment stays usable.
+                                        let path = folder.join("data.mdb");
+                                        env.copy_to_file(&path, CompactionOption::Enabled)
+                                            .with_context(|| {
+                                                format!("while writing {}", path.display())
+                                            })?;
+                                        Ok(format!(
+                                            "Compacted the environment into {}",
+                                            folder.display()
+                                        ))
+                                    }));
+                            }
                         }
                     }
-                    Some(count)
-                } else {
-                    None
-                };
+                }
+
+                match backup {
+                    Some((task, path)) => match task.try_finish() {
+                        Some(Ok(message)) => {
+                            self.toasts.add(message);
+                            *backup = None;
+                        }
+                        Some(Err(e)) => {
+                            self.toasts.add(format!("The backup failed: {e:#}"));
+                            *backup = None;
+                        }
+                        None => {
+                            // LMDB does not report the progress of a copy, the size of the
+                            // destination file is compared to the one of the environment.
+                            let written = std::fs::metadata(&path).map_or(0, |m| m.len() as usize);
+                            let total = env.real_disk_size().map_or(0, |size| size as usize);
+                            let cancelled = ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!(
+                                    "Backing up the environment… {} of {}",
+                                    byte_size::format(written),
+                                    byte_size::format(total)
+                                ));
+                                let cancel = ui.button("Cancel").on_hover_text(
+                                    "LMDB cannot interrupt a copy, it continues in the background \
+                                    and the destination file may be incomplete",
+                                );
+                                cancel.clicked()
+                            });
+                            if cancelled.inner {
+                                self.toasts.add(format!(
+                                    "The backup was detached, {} may be incomplete",
+                                    path.display()
+                                ));
+                                *backup = None;
+                            }
+                            ui.ctx().request_repaint();
+                        }
+                    },
+                    None => {
+                        let button = ui.button("Backup to…").on_hover_text(
+                            "Copies the environment as-is into a file, free pages included",
+                        );
+                        if button.clicked() {
+                            let file = rfd::FileDialog::new().set_file_name("data.mdb").save_file();
+                            if let Some(path) = file {
+                                let destination = path.clone();
+                                let task = BackgroundTask::spawn("Backing up", move |_| {
+                                    // heed creates the file and fails if it already exists.
+                                    if destination.exists() {
+                                        std::fs::remove_file(&destination)?;
+                                    }
+                                    env.copy_to_file(&destination, CompactionOption::Disabled)
+                                        .with_context(|| {
+                                            format!("while writing {}", destination.display())
+                                        })?;
+                                    Ok(format!(
+                                        "Backed up the environment to {}",
+                                        destination.display()
+                                    ))
+                                });
+                                *backup = Some((task, path));
+                            }
+                        }
+                    }
+                }
+            }
+            Pane::DatabaseStats { database, stats, .. } => {
+                if ui.button("recompute").clicked() {
+                    *stats = None;
+                }
 
-                let num_rows = database.len(rtxn).unwrap().try_into().unwrap();
-                let mut prev_row_index = None;
-                let mut iter = database.iter(rtxn).unwrap();
+                let stats = stats.get_or_insert_with(|| {
+                    DatabaseStats::compute(*database, self.txn.read_txn())
+                        .report(self.errors, "Could not compute the statistics")
+                        .unwrap_or_default()
+                });
 
-                let builder = match scroll_to {
-                    Some(row) => TableBuilder::new(ui).scroll_to_row(row, Some(Align::TOP)),
-                    None => TableBuilder::new(ui),
-                };
+                if stats.truncated {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "Stopped after {} entries, the statistics are partial.",
+                            stats::MAX_ENTRIES
+                        ),
+                    );
+                }
+
+                egui::Grid::new("database_stats").num_columns(2).striped(true).show(ui, |ui| {
+                    ui.label("Entries");
+                    ui.label(stats.entries.to_string());
+                    ui.end_row();
+
+                    ui.label("Total key bytes");
+                    ui.label(stats.total_key_bytes.to_string());
+                    ui.end_row();
+
+                    ui.label("Total value bytes");
+                    ui.label(stats.total_value_bytes.to_string());
+                    ui.end_row();
+
+                    ui.label("Key length (min/avg/max)");
+                    ui.label(format!(
+                        "{} / {:.2} / {}",
+                        stats.min_key_len,
+                        stats.average_key_len(),
+                        stats.max_key_len
+                    ));
+                    ui.end_row();
+
+                    ui.label("Value length (min/avg/max)");
+                    ui.label(format!(
+                        "{} / {:.2} / {}",
+                        stats.min_value_len,
+                        stats.average_value_len(),
+                        stats.max_value_len
+                    ));
+                    ui.end_row();
+
+                    ui.label("Value entropy")
+                        .on_hover_text("Close to 8 bits per byte for compressed or encrypted data, lower for structured text");
+                    let sampled = if stats.total_value_bytes > stats::ENTROPY_SAMPLE_BYTES {
+                        format!(" (first {})", byte_size::format(stats::ENTROPY_SAMPLE_BYTES))
+                    } else {
+                        String::new()
+                    };
+                    ui.label(format!("{:.2} bits/byte{sampled}", stats.value_entropy));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.label("Key lengths");
+                length_histogram_ui(ui, "key_len_histogram", &stats.key_len_histogram);
+                ui.label("Value lengths");
+                length_histogram_ui(ui, "value_len_histogram", &stats.value_len_histogram);
+            }
+            Pane::Diff { left, left_name, right, right_name, diff } => {
+                if ui.button("recompute").clicked() {
+                    *diff = None;
+                }
 
-                builder
-                    .column(Column::exact(65.0).resizable(false))
-                    .column(
-                        Column::auto_with_initial_suggestion(100.0)
-                            .at_least(100.0)
-                            .clip(true)
-                            .resizable(true),
-                    )
-                    .column(Column::remainder().at_least(50.0).clip(true).resizable(true))
+                let diff = diff.get_or_insert_with(|| {
+                    DatabaseDiff::compute(*left, *right, self.txn.read_txn())
+                        .report(self.errors, "Could not compute the diff")
+                        .unwrap_or_default()
+                });
+
+                if diff.truncated {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "Stopped after {} differences, the comparison is partial.",
+                            diff::MAX_DIFFERENCES
+                        ),
+                    );
+                }
+
+                let left_name = left_name.as_deref().unwrap_or("{main}");
+                let right_name = right_name.as_deref().unwrap_or("{main}");
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Color32::RED,
+                        format!("{} only in {left_name}", diff.only_in_left),
+                    );
+                    ui.separator();
+                    ui.colored_label(
+                        Color32::GREEN,
+                        format!("{} only in {right_name}", diff.only_in_right),
+                    );
+                    ui.separator();
+                    ui.colored_label(Color32::YELLOW, format!("{} differ", diff.differs));
+                });
+
+                TableBuilder::new(ui)
+                    .column(Column::auto().at_least(100.0).clip(true).resizable(true))
+                    .column(Column::auto().at_least(100.0).clip(true).resizable(true))
+                    .column(Column::remainder().at_least(100.0).clip(true))
                     .header(20.0, |mut header| {
                         header.col(|ui| {
-                            ui.label("Operations");
+                            ui.label("Keys");
                         });
                         header.col(|ui| {
-                            ui.label("Keys");
+                            ui.label(left_name);
                         });
                         header.col(|ui| {
-                            ui.label("Values");
+                            ui.label(right_name);
                         });
                     })
                     .body(|body| {
-                        body.rows(30.0, num_rows, |mut row| {
-                            let row_index = row.index();
-                            assert!(prev_row_index.map_or(true, |p| p + 1 == row_index));
-                            if prev_row_index.is_none() {
-                                iter.by_ref().take(row_index).for_each(drop);
-                            }
-                            prev_row_index = Some(row_index);
-
-                            if let Some(result) = iter.next() {
-                                let (key, data) = result.unwrap();
-                                let encoded_key = stfu8::encode_u8_pretty(key);
-                                let encoded_data = stfu8::encode_u8_pretty(data);
-
-                                row.col(|ui| {
-                                    // TODO Replace me by a ✏️
-                                    if ui.button("edit").clicked() {
-                                        entry_to_insert.key = encoded_key.clone();
-                                        entry_to_insert.data = encoded_data.clone();
-                                    }
-                                    // // Replace me by a red 🗑️
-                                    // if ui.button("delete").clicked() {
-                                    //     if let Some(wtxn) = self.wtxn.as_mut() {
-                                    //     }
-                                    // }
-                                });
-                                row.col(|ui| {
-                                    ui.label(&encoded_key);
-                                });
-                                row.col(|ui| {
-                                    ui.label(&encoded_data);
-                                });
-                            }
+                        body.rows(30.0, diff.entries.len(), |mut row| {
+                            let entry = &diff.entries[row.index()];
+                            let color = match entry.kind {
+                                DiffKind::OnlyInLeft => Color32::RED,
+                                DiffKind::OnlyInRight => Color32::GREEN,
+                                DiffKind::Differs => Color32::YELLOW,
+                            };
+                            let encode = |bytes: &Option<Vec<u8>>| match bytes {
+                                Some(bytes) => stfu8::encode_u8_pretty(bytes),
+                                None => String::new(),
+                            };
+                            row.col(|ui| {
+                                ui.colored_label(color, stfu8::encode_u8_pretty(&entry.key));
+                            });
+                            row.col(|ui| {
+                                ui.colored_label(color, encode(&entry.left_value));
+                            });
+                            row.col(|ui| {
+                                ui.colored_label(color, encode(&entry.right_value));
+                            });
                         });
                     });
             }
-            Pane::OpenNew { database_to_open } => {
-                let response = ui.horizontal(|ui| {
-                    // If there is a write txn opened, use it, otherwise make the wtxn live longer and deref it.
-                    let long_wtxn: &RwTxn;
-                    let rtxn = match self.txn {
-                        txn::Txn::Ro(ref rtxn) => rtxn,
-                        txn::Txn::Rw(ref wtxn) => {
-                            long_wtxn = wtxn;
-                            long_wtxn.deref()
+            Pane::FindReplace(find_replace) => find_replace.ui(self, ui),
+            Pane::RawCursor(cursor) => cursor.ui(self, ui),
+            Pane::Snapshots(snapshots) => snapshots.ui(self, ui),
+            Pane::Bookmarks => {
+                self.bookmarks.retain(|_, keys| !keys.is_empty());
+                if self.bookmarks.is_empty() {
+                    ui.label("No bookmarks, pin an entry with 📌 to keep it here across sessions.");
+                    return egui_tiles::UiResponse::None;
+                }
+
+                let rtxn = self.txn.read_txn();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (name, keys) in self.bookmarks.iter_mut() {
+                        ui.push_id(name, |ui| {
+                            let label = name.as_deref().unwrap_or("{main}");
+                            match self.env.open_database(rtxn, name.as_deref()) {
+                                Ok(Some(database)) => {
+                                    ui.horizontal(|ui| {
+                                        ui.heading(label);
+                                        if ui.button("open").clicked() {
+                                            let pane = Pane::database_entries(
+                                                self.env,
+                                                rtxn,
+                                                name.clone(),
+                                                database,
+                                            );
+                                            self.pane_to_open = Some((tile_id, pane));
+                                        }
+                                    });
+                                    database_entries::pinned_entries_ui(
+                                        ui,
+                                        database,
+                                        rtxn,
+                                        keys,
+                                        DisplayMode::default(),
+                                        false,
+                                    );
+                                }
+                                Ok(None) => {
+                                    ui.heading(label);
+                                    ui.weak("This database no longer exists.");
+                                }
+                                Err(e) => {
+                                    ui.heading(label);
+                                    ui.colored_label(Color32::RED, e.to_string());
+                                }
+                            }
+                        });
+                        ui.separator();
+                    }
+                });
+            }
+            Pane::KeyWatch { database_name, database, key, history, interval_secs, last_poll } => {
+                // The key is only read while the tab is visible, a fresh read transaction
+                // sees the writes committed since the workspace one was opened.
+                let interval = Duration::from_secs(*interval_secs);
+                let elapsed = last_poll.map(|last_poll| last_poll.elapsed());
+                if elapsed.is_none_or(|elapsed| elapsed >= interval) {
+                    let value = self
+                        .env
+                        .read_txn()
+                        .and_then(|rtxn| Ok(database.get(&rtxn, key)?.map(<[u8]>::to_vec)))
+                        .report(self.errors, "Could not read the watched key");
+                    if let Some(value) = value {
+                        if history.back().is_none_or(|(_, last)| *last != value) {
+                            history.push_back((Instant::now(), value));
+                            if history.len() > MAX_WATCHED_VALUES {
+                                history.pop_front();
+                            }
                         }
-                        txn::Txn::None => unreachable!(),
-                    };
+                    }
+                    *last_poll = Some(Instant::now());
+                    ui.ctx().request_repaint_after(interval);
+                } else if let Some(elapsed) = elapsed {
+                    ui.ctx().request_repaint_after(interval - elapsed);
+                }
 
-                    ui.add(egui::TextEdit::singleline(database_to_open).hint_text("database name"));
-                    if ui.button("open").clicked() {
-                        let env = ENV.get().unwrap();
-                        let database_name = if database_to_open.is_empty() {
-                            None
-                        } else {
-                            Some(mem::take(database_to_open))
-                        };
+                ui.horizontal(|ui| {
+                    let name = database_name.as_deref().unwrap_or("{main}");
+                    ui.label(format!("Watching {} in {name} every", stfu8::encode_u8_pretty(key)));
+                    ui.add(egui::DragValue::new(interval_secs).clamp_range(1..=3600).suffix("s"));
+                });
+                ui.separator();
 
-                        env.open_database(rtxn, database_name.as_ref().map(AsRef::as_ref))
-                            .unwrap()
-                            .map(|database| Pane::DatabaseEntries {
-                                database,
-                                database_name,
-                                entry_to_insert: Default::default(),
-                                jump_to_key: String::new(),
-                            })
-                    } else {
-                        None
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("watched_values").striped(true).num_columns(2).show(ui, |ui| {
+                        for (instant, value) in history.iter().rev() {
+                            ui.weak(format!("{}s ago", instant.elapsed().as_secs()));
+                            match value {
+                                Some(value) => ui.add(
+                                    egui::Label::new(stfu8::encode_u8_pretty(value)).truncate(true),
+                                ),
+                                None => ui.weak("missing"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+            Pane::TransactionLog => {
+                let Txn::Rw(wtxn) = &mut *self.txn else {
+                    ui.label(
+                        "No write transaction in progress, start writing to record the changes.",
+                    );
+                    return egui_tiles::UiResponse::None;
+                };
+
+                let history = &mut *self.history;
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} changes in this transaction", history.entries().len()));
+                    let undo = ui
+                        .add_enabled(
+                            !self.read_only && !history.entries().is_empty(),
+                            egui::Button::new("Undo last"),
+                        )
+                        .on_hover_text(ui.ctx().format_shortcut(&UNDO_SHORTCUT));
+                    if undo.clicked() {
+                        history.undo(wtxn).report(self.errors, "Could not undo the change");
+                    }
+                    let redo = ui
+                        .add_enabled(
+                            !self.read_only && !history.undone().is_empty(),
+                            egui::Button::new("Redo"),
+                        )
+                        .on_hover_text(ui.ctx().format_shortcut(&REDO_SHORTCUT));
+                    if redo.clicked() {
+                        history.redo(wtxn).report(self.errors, "Could not redo the change");
                     }
                 });
+                ui.label("The bulk operations, imports and scripts are not recorded.");
+                ui.separator();
 
-                if let InnerResponse { inner: Some(p), .. } = response {
-                    *pane = p;
-                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, entry) in history.entries().iter().enumerate().rev() {
+                        history_entry_ui(ui, i, entry);
+                    }
+                    if !history.undone().is_empty() {
+                        ui.separator();
+                        ui.weak(format!("{} undone changes", history.undone().len()));
+                    }
+                });
+            }
+            Pane::ScriptConsole { language, database, script, output, .. } => {
+                ui.add(
+                    egui::TextEdit::multiline(script)
+                        .code_editor()
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(10)
+                        .hint_text(language.sample()),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.button("Run").clicked() {
+                        let (result, mutated) =
+                            language.run(script, self.txn, *database, self.read_only);
+                        *output = result;
+                        self.txn_changed |= mutated;
+                        if mutated {
+                            // We do not know how many entries the script changed.
+                            self.history.record_untracked(1);
+                        }
+                    }
+                    if !matches!(self.txn, Txn::Rw(_)) {
+                        ui.label("The database can only be modified in a write transaction");
+                    }
+                });
+
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut output.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
             }
         }
 
         egui_tiles::UiResponse::None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use egui_tiles::{Container, ContainerKind, Tile, Tiles, Tree};
+
+    use super::simplification_options;
+
+    /// Returns the kind and children of the container tile.
+    fn container(
+        tree: &Tree<&'static str>,
+        tile_id: egui_tiles::TileId,
+    ) -> (ContainerKind, Vec<&'static str>) {
+        let Some(Tile::Container(container)) = tree.tiles.get(tile_id) else {
+            panic!("{tile_id:?} is not a container");
+        };
+        let panes = container
+            .children()
+            .map(|&child| match tree.tiles.get(child) {
+                Some(Tile::Pane(pane)) => *pane,
+                other => panic!("{child:?} is not a pane: {other:?}"),
+            })
+            .collect();
+        (container.kind(), panes)
+    }
+
+    #[test]
+    fn drag_tab_between_split_containers() {
+        // Two databases side by side, each one in its own tabs container.
+        let mut tiles = Tiles::default();
+        let left_pane = tiles.insert_pane("left");
+        let right_pane = tiles.insert_pane("right");
+        let left = tiles.insert_tab_tile(vec![left_pane]);
+        let right = tiles.insert_tab_tile(vec![right_pane]);
+        let root = tiles.insert_horizontal_tile(vec![left, right]);
+        let mut tree = Tree::new("tree", root, tiles);
+
+        // Dropping the right tab after the left one, like the drag and drop of egui_tiles.
+        tree.move_tile_to_container(right_pane, left, 1, false);
+        tree.simplify(&simplification_options());
+
+        // The emptied tabs container and the single child horizontal one are pruned.
+        let root = tree.root().unwrap();
+        assert_eq!(root, left);
+        assert_eq!(container(&tree, root), (ContainerKind::Tabs, vec!["left", "right"]));
+        assert!(tree.tiles.get(right).is_none());
+        if let Some(Tile::Container(Container::Tabs(tabs))) = tree.tiles.get(root) {
+            assert_eq!(tabs.active, Some(right_pane));
+        }
+    }
+
+    #[test]
+    fn drag_tab_to_reorder() {
+        let mut tiles = Tiles::default();
+        let first = tiles.insert_pane("first");
+        let second = tiles.insert_pane("second");
+        let root = tiles.insert_tab_tile(vec![first, second]);
+        let mut tree = Tree::new("tree", root, tiles);
+
+        tree.move_tile_to_container(second, root, 0, false);
+        tree.simplify(&simplification_options());
+
+        assert_eq!(tree.root(), Some(root));
+        assert_eq!(container(&tree, root), (ContainerKind::Tabs, vec!["second", "first"]));
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+
+use heed::types::{Bytes, DecodeIgnore};
+use heed::{Database, RoTxn};
+
+/// The state of the window showing the number of entries by key prefix.
+pub struct PrefixGroups {
+    /// The number of bytes of the keys the entries are grouped by.
+    pub prefix_len: usize,
+    /// The prefixes in key order with their number of entries, indexed by the displayed rows.
+    pub counts: heed::Result<Vec<(Vec<u8>, u64)>>,
+}
+
+impl PrefixGroups {
+    pub fn new(database: Database<Bytes, Bytes>, rtxn: &RoTxn, prefix_len: usize) -> PrefixGroups {
+        let counts = count_by_prefix(database, rtxn, prefix_len);
+        PrefixGroups { prefix_len, counts: counts.map(|counts| counts.into_iter().collect()) }
+    }
+}
+
+/// Counts the entries by the first `prefix_len` bytes of their keys, shorter keys are their own prefix.
+pub fn count_by_prefix(
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    prefix_len: usize,
+) -> heed::Result<BTreeMap<Vec<u8>, u64>> {
+    let mut counts = BTreeMap::new();
+    for result in database.remap_data_type::<DecodeIgnore>().iter(rtxn)? {
+        let (key, ()) = result?;
+        let prefix = &key[..key.len().min(prefix_len)];
+        match counts.get_mut(prefix) {
+            Some(count) => *count += 1,
+            None => {
+                counts.insert(prefix.to_vec(), 1);
+            }
+        }
+    }
+    Ok(counts)
+}
@@ -0,0 +1,150 @@
+use std::ops::Bound;
+
+use eframe::egui;
+use egui::Color32;
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+
+use crate::errors::ReportError;
+use crate::TreeBehavior;
+
+/// A cursor operation, named after the LMDB one it mimics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CursorOp {
+    /// `MDB_FIRST`
+    First,
+    /// `MDB_LAST`
+    Last,
+    /// `MDB_NEXT`
+    Next,
+    /// `MDB_PREV`
+    Prev,
+    /// `MDB_SET_KEY`
+    SeekKey,
+    /// `MDB_SET_RANGE`
+    SeekRange,
+}
+
+/// A pane moving a cursor over the entries of a database one operation at a time.
+pub struct RawCursor {
+    pub database_name: Option<String>,
+    pub database: Database<Bytes, Bytes>,
+    /// The entry the cursor is on, `None` until the first operation.
+    position: Option<(Vec<u8>, Vec<u8>)>,
+    /// The escaped key to seek.
+    seek_key: String,
+    /// The outcome of the last operation when it did not move the cursor.
+    status: Option<String>,
+}
+
+impl RawCursor {
+    pub fn new(database_name: Option<String>, database: Database<Bytes, Bytes>) -> RawCursor {
+        RawCursor { database_name, database, position: None, seek_key: String::new(), status: None }
+    }
+
+    pub fn ui(&mut self, behavior: &mut TreeBehavior, ui: &mut egui::Ui) {
+        let mut op = None;
+        ui.horizontal(|ui| {
+            if ui.button("First").clicked() {
+                op = Some(CursorOp::First);
+            }
+            if ui.button("Last").clicked() {
+                op = Some(CursorOp::Last);
+            }
+            // Like LMDB, moving an unpositioned cursor starts from the first or last entry.
+            if ui.button("Prev").clicked() {
+                op = Some(CursorOp::Prev);
+            }
+            if ui.button("Next").clicked() {
+                op = Some(CursorOp::Next);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.seek_key).hint_text("escaped key"));
+            if ui.button("Seek to key").on_hover_text("Positions on this exact key").clicked() {
+                op = Some(CursorOp::SeekKey);
+            }
+            let seek_range =
+                ui.button("Seek to key≥").on_hover_text("Positions on the first key not before");
+            if seek_range.clicked() {
+                op = Some(CursorOp::SeekRange);
+            }
+        });
+
+        if let Some(op) = op {
+            let seek_key = match op {
+                CursorOp::SeekKey | CursorOp::SeekRange => stfu8::decode_u8(&self.seek_key),
+                _ => Ok(Vec::new()),
+            };
+            match seek_key {
+                Ok(seek_key) => {
+                    let rtxn = behavior.txn.read_txn();
+                    let entry = self
+                        .execute(rtxn, op, &seek_key)
+                        .report(behavior.errors, "Could not move the cursor");
+                    match entry {
+                        Some(Some(entry)) => {
+                            self.position = Some(entry);
+                            self.status = None;
+                        }
+                        // The cursor does not move when LMDB returns `MDB_NOTFOUND`.
+                        Some(None) => self.status = Some("MDB_NOTFOUND".to_owned()),
+                        None => (),
+                    }
+                }
+                Err(e) => self.status = Some(format!("invalid key: {e}")),
+            }
+        }
+        ui.separator();
+
+        match &self.position {
+            Some((key, value)) => {
+                egui::Grid::new("raw_cursor_position").num_columns(2).show(ui, |ui| {
+                    ui.strong("Key");
+                    ui.label(
+                        egui::RichText::new(stfu8::encode_u8_pretty(key)).monospace().heading(),
+                    );
+                    ui.end_row();
+                    ui.strong("Value");
+                    ui.label(egui::RichText::new(stfu8::encode_u8_pretty(value)).monospace());
+                    ui.end_row();
+                });
+            }
+            None => {
+                ui.weak("The cursor is not positioned yet.");
+            }
+        }
+        if let Some(status) = &self.status {
+            ui.colored_label(Color32::YELLOW, status);
+        }
+    }
+
+    /// Runs the operation and returns the entry the cursor lands on, if any.
+    fn execute(
+        &self,
+        rtxn: &RoTxn,
+        op: CursorOp,
+        seek_key: &[u8],
+    ) -> heed::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        let database = self.database;
+        let current = self.position.as_ref().map(|(key, _)| key.as_slice());
+        let entry = match (op, current) {
+            (CursorOp::First, _) | (CursorOp::Next, None) => database.first(rtxn)?,
+            (CursorOp::Last, _) | (CursorOp::Prev, None) => database.last(rtxn)?,
+            (CursorOp::Next, Some(key)) => {
+                let range = (Bound::Excluded(key), Bound::Unbounded);
+                database.range(rtxn, &range)?.next().transpose()?
+            }
+            (CursorOp::Prev, Some(key)) => {
+                let range = (Bound::Unbounded, Bound::Excluded(key));
+                database.rev_range(rtxn, &range)?.next().transpose()?
+            }
+            (CursorOp::SeekKey, _) => database.get(rtxn, seek_key)?.map(|value| (seek_key, value)),
+            (CursorOp::SeekRange, _) => {
+                let range = (Bound::Included(seek_key), Bound::Unbounded);
+                database.range(rtxn, &range)?.next().transpose()?
+            }
+        };
+        Ok(entry.map(|(key, value)| (key.to_vec(), value.to_vec())))
+    }
+}
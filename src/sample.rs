@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+
+/// The number of random bytes appended to the common prefix of the keys to seek.
+const RANDOM_SUFFIX_LEN: usize = 8;
+
+/// The state of the window showing random entries of a database.
+pub struct Sample {
+    /// The number of entries to sample.
+    pub count: usize,
+    pub entries: heed::Result<Vec<(Vec<u8>, Vec<u8>)>>,
+}
+
+impl Sample {
+    pub fn new(database: Database<Bytes, Bytes>, rtxn: &RoTxn, count: usize) -> Sample {
+        Sample { count, entries: sample(database, rtxn, count) }
+    }
+}
+
+/// Returns up to `count` distinct entries, sorted by key, without scanning the database.
+///
+/// LMDB cannot access an entry by position, so we seek to the first key greater than or
+/// equal to random keys instead. The random keys share the prefix of the first and last keys
+/// of the database, otherwise most of them would land before or after every key.
+pub fn sample(
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    count: usize,
+) -> heed::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let (Some((first, _)), Some((last, _))) = (database.first(rtxn)?, database.last(rtxn)?) else {
+        return Ok(Vec::new());
+    };
+    let common_len = first.iter().zip(last).take_while(|(a, b)| a == b).count();
+    let prefix = &first[..common_len];
+
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        let mut random_key = prefix.to_vec();
+        let mut suffix = [0; RANDOM_SUFFIX_LEN];
+        rand::fill(&mut suffix);
+        random_key.extend_from_slice(&suffix);
+
+        let range = (Bound::Included(random_key.as_slice()), Bound::Unbounded);
+        let entry = match database.range(rtxn, &range)?.next() {
+            Some(result) => result?,
+            // The random key is after the last one, we wrap around.
+            None => database.first(rtxn)?.unwrap(),
+        };
+        entries.insert(entry.0.to_vec(), entry.1.to_vec());
+    }
+
+    Ok(entries.into_iter().collect())
+}
@@ -0,0 +1,371 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ops::Bound;
+use std::rc::Rc;
+
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+use mlua::{HookTriggers, Lua, LuaString, MultiValue, Value, VmState};
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+
+use crate::database_entries::prefix_entries;
+use crate::txn::{DirtyRwTxn, Txn};
+
+/// The number of instructions a Lua script can run before being stopped.
+const LUA_MAX_INSTRUCTIONS: u32 = 100_000_000;
+
+/// The number of instructions between two checks of the Lua instruction count.
+const LUA_HOOK_INTERVAL: u32 = 10_000;
+
+/// The number of entries `db.iter` reads at once, a chunk also ends with all the values of
+/// its last key.
+const LUA_ITER_CHUNK_SIZE: usize = 1000;
+
+/// Builds `db.iter` on top of the `db.chunk` function and hides the latter.
+///
+/// The iterator lazily reads the entries by chunks, `db.chunk` returns the keys and values
+/// in a flat table followed by the last key when there are more entries to read.
+const LUA_ITER: &str = r#"
+local chunk = db.chunk
+db.chunk = nil
+function db.iter(prefix)
+    local entries, after = chunk(prefix)
+    local index = -1
+    return function()
+        index = index + 2
+        if entries[index] == nil and after ~= nil then
+            entries, after = chunk(prefix, after)
+            index = 1
+        end
+        return entries[index], entries[index + 1]
+    end
+end
+"#;
+
+/// The number of operations a Rhai script can run before being stopped.
+const RHAI_MAX_OPERATIONS: u64 = 100_000_000;
+
+/// The depth of function calls a Rhai script can reach.
+const RHAI_MAX_CALL_LEVELS: usize = 64;
+
+/// The languages a script console can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLanguage {
+    Lua,
+    Rhai,
+}
+
+impl ScriptLanguage {
+    pub const ALL: [ScriptLanguage; 2] = [ScriptLanguage::Lua, ScriptLanguage::Rhai];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Lua => "Lua",
+            ScriptLanguage::Rhai => "Rhai",
+        }
+    }
+
+    /// A sample script showing the functions available to the scripts.
+    pub fn sample(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Lua => {
+                "for key, value in db.iter(\"prefix\") do\n    print(key, #value)\nend"
+            }
+            ScriptLanguage::Rhai => {
+                "for entry in db_iter(\"prefix\") {\n    print(`${entry[0]}: ${entry[1]}`);\n}"
+            }
+        }
+    }
+
+    /// Runs the script, see [`run_lua`] and [`run_rhai`].
+    ///
+    /// The puts and deletes of the script fail when `read_only` is set.
+    pub fn run(
+        &self,
+        script: &str,
+        txn: &mut Txn,
+        database: Database<Bytes, Bytes>,
+        read_only: bool,
+    ) -> (String, bool) {
+        match self {
+            ScriptLanguage::Lua => run_lua(script, txn, database, read_only),
+            ScriptLanguage::Rhai => run_rhai(script, txn, database, read_only),
+        }
+    }
+}
+
+/// Runs a Lua script on the database and returns what it printed followed by its return values.
+///
+/// The script gets a `db` table with `get(key)`, `put(key, value)`, `delete(key)` and
+/// `iter(prefix)` functions, the last one is meant to be used in a generic `for` loop.
+/// The script is stopped with an error after [`LUA_MAX_INSTRUCTIONS`] instructions.
+/// The boolean tells whether the script modified the database.
+pub fn run_lua(
+    script: &str,
+    txn: &mut Txn,
+    database: Database<Bytes, Bytes>,
+    read_only: bool,
+) -> (String, bool) {
+    let lua = Lua::new();
+    let executed = Cell::new(0u32);
+    let hook =
+        lua.set_hook(HookTriggers::new().every_nth_instruction(LUA_HOOK_INTERVAL), move |_, _| {
+            executed.set(executed.get() + LUA_HOOK_INTERVAL);
+            if executed.get() >= LUA_MAX_INSTRUCTIONS {
+                Err(mlua::Error::runtime(format!(
+                    "the script ran more than {LUA_MAX_INSTRUCTIONS} instructions"
+                )))
+            } else {
+                Ok(VmState::Continue)
+            }
+        });
+    if let Err(e) = hook {
+        return (format!("error: {e}"), false);
+    }
+    let output = RefCell::new(String::new());
+    let mutated = Cell::new(false);
+    let txn = RefCell::new(txn);
+
+    let result = lua.scope(|scope| {
+        let print = scope.create_function(|_, args: MultiValue| {
+            let line: Vec<_> = args.iter().map(lua_to_string).collect();
+            let mut output = output.borrow_mut();
+            output.push_str(&line.join("\t"));
+            output.push('\n');
+            Ok(())
+        })?;
+        lua.globals().set("print", print)?;
+
+        let db = lua.create_table()?;
+        db.set(
+            "get",
+            scope.create_function(|lua, key: LuaString| {
+                let txn = txn.borrow();
+                let value =
+                    database.get(txn.read_txn(), &key.as_bytes()).map_err(mlua::Error::external)?;
+                match value {
+                    Some(value) => lua.create_string(value).map(Value::String),
+                    None => Ok(Value::Nil),
+                }
+            })?,
+        )?;
+        db.set(
+            "put",
+            scope.create_function(|_, (key, value): (LuaString, LuaString)| {
+                let mut txn = txn.borrow_mut();
+                let wtxn = write_txn(&mut txn, read_only)?;
+                database
+                    .put(wtxn, &key.as_bytes(), &value.as_bytes())
+                    .map_err(mlua::Error::external)?;
+                wtxn.mark_dirty();
+                mutated.set(true);
+                Ok(())
+            })?,
+        )?;
+        db.set(
+            "delete",
+            scope.create_function(|_, key: LuaString| {
+                let mut txn = txn.borrow_mut();
+                let wtxn = write_txn(&mut txn, read_only)?;
+                let deleted =
+                    database.delete(wtxn, &key.as_bytes()).map_err(mlua::Error::external)?;
+                if deleted {
+                    wtxn.mark_dirty();
+                }
+                mutated.set(mutated.get() || deleted);
+                Ok(deleted)
+            })?,
+        )?;
+        db.set(
+            "chunk",
+            scope.create_function(
+                |lua, (prefix, after): (Option<LuaString>, Option<LuaString>)| {
+                    let txn = txn.borrow();
+                    let prefix = prefix.map_or_else(Vec::new, |p| p.as_bytes().to_vec());
+                    let after = after.map(|a| a.as_bytes().to_vec());
+                    let mut entries = Vec::with_capacity(LUA_ITER_CHUNK_SIZE);
+                    let more = read_chunk(
+                        database,
+                        txn.read_txn(),
+                        &prefix,
+                        after.as_deref(),
+                        &mut entries,
+                    )
+                    .map_err(mlua::Error::external)?;
+                    let last_key = entries.last().filter(|_| more).map(|(key, _)| key.clone());
+                    let table = lua.create_table_with_capacity(entries.len() * 2, 0)?;
+                    for (key, value) in entries {
+                        table.raw_push(lua.create_string(key)?)?;
+                        table.raw_push(lua.create_string(value)?)?;
+                    }
+                    let last_key = last_key.map(|key| lua.create_string(key)).transpose()?;
+                    Ok((table, last_key))
+                },
+            )?,
+        )?;
+        lua.globals().set("db", db)?;
+        lua.load(LUA_ITER).set_name("db.iter").exec()?;
+
+        lua.load(script).set_name("script").eval::<MultiValue>()
+    });
+
+    let mut output = output.into_inner();
+    match result {
+        Ok(values) if values.is_empty() => (),
+        Ok(values) => {
+            let values: Vec<_> = values.iter().map(lua_to_string).collect();
+            output.push_str(&format!("=> {}", values.join("\t")));
+        }
+        Err(e) => output.push_str(&format!("error: {e}")),
+    }
+
+    (output, mutated.get())
+}
+
+/// Runs a Rhai script on the database and returns what it printed followed by its return value.
+///
+/// The script gets the `db_get(key)`, `db_put(key, value)`, `db_delete(key)` and
+/// `db_iter(prefix)` functions, keys and values are stfu8-encoded strings and `db_iter`
+/// returns an array of `[key, value]` pairs. The script is stopped with an error after
+/// [`RHAI_MAX_OPERATIONS`] operations or [`RHAI_MAX_CALL_LEVELS`] nested calls.
+/// The boolean tells whether the script modified the database.
+pub fn run_rhai(
+    script: &str,
+    txn: &mut Txn,
+    database: Database<Bytes, Bytes>,
+    read_only: bool,
+) -> (String, bool) {
+    // Rhai functions must be 'static, we lend them the transaction and take it back at the end.
+    let shared_txn = Rc::new(RefCell::new(mem::replace(txn, Txn::None)));
+    let output = Rc::new(RefCell::new(String::new()));
+    let mutated = Rc::new(Cell::new(false));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(RHAI_MAX_OPERATIONS);
+    engine.set_max_call_levels(RHAI_MAX_CALL_LEVELS);
+    let printed = output.clone();
+    engine.on_print(move |text| {
+        let mut output = printed.borrow_mut();
+        output.push_str(text);
+        output.push('\n');
+    });
+
+    let get_txn = shared_txn.clone();
+    engine.register_fn("db_get", move |key: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+        let key = decode_rhai(key)?;
+        let txn = get_txn.borrow();
+        match database.get(txn.read_txn(), &key).map_err(|e| e.to_string())? {
+            Some(value) => Ok(stfu8::encode_u8(value).into()),
+            None => Ok(Dynamic::UNIT),
+        }
+    });
+
+    let (put_txn, put_mutated) = (shared_txn.clone(), mutated.clone());
+    engine.register_fn("db_put", move |key: &str, value: &str| -> Result<(), Box<EvalAltResult>> {
+        let (key, value) = (decode_rhai(key)?, decode_rhai(value)?);
+        let mut txn = put_txn.borrow_mut();
+        let wtxn = write_txn(&mut txn, read_only).map_err(|e| e.to_string())?;
+        database.put(wtxn, &key, &value).map_err(|e| e.to_string())?;
+        wtxn.mark_dirty();
+        put_mutated.set(true);
+        Ok(())
+    });
+
+    let (delete_txn, delete_mutated) = (shared_txn.clone(), mutated.clone());
+    engine.register_fn("db_delete", move |key: &str| -> Result<bool, Box<EvalAltResult>> {
+        let key = decode_rhai(key)?;
+        let mut txn = delete_txn.borrow_mut();
+        let wtxn = write_txn(&mut txn, read_only).map_err(|e| e.to_string())?;
+        let deleted = database.delete(wtxn, &key).map_err(|e| e.to_string())?;
+        if deleted {
+            wtxn.mark_dirty();
+        }
+        delete_mutated.set(delete_mutated.get() || deleted);
+        Ok(deleted)
+    });
+
+    let iter_txn = shared_txn.clone();
+    engine.register_fn("db_iter", move |prefix: &str| -> Result<Array, Box<EvalAltResult>> {
+        let prefix = decode_rhai(prefix)?;
+        let txn = iter_txn.borrow();
+        let mut entries = Array::new();
+        for result in
+            prefix_entries(database, txn.read_txn(), &prefix).map_err(|e| e.to_string())?
+        {
+            let (key, value) = result.map_err(|e| e.to_string())?;
+            let pair: Array = vec![stfu8::encode_u8(key).into(), stfu8::encode_u8(value).into()];
+            entries.push(pair.into());
+        }
+        Ok(entries)
+    });
+
+    let result = engine.eval::<Dynamic>(script);
+    drop(engine);
+
+    let mut output = Rc::try_unwrap(output).map(RefCell::into_inner).unwrap_or_default();
+    match result {
+        Ok(value) if value.is_unit() => (),
+        Ok(value) => output.push_str(&format!("=> {value}")),
+        Err(e) => output.push_str(&format!("error: {e}")),
+    }
+
+    *txn = match Rc::try_unwrap(shared_txn) {
+        Ok(shared_txn) => shared_txn.into_inner(),
+        Err(_) => unreachable!("the engine holding the transaction was dropped"),
+    };
+
+    (output, mutated.get())
+}
+
+fn decode_rhai(text: &str) -> Result<Vec<u8>, Box<EvalAltResult>> {
+    stfu8::decode_u8(text).map_err(|e| format!("invalid stfu8 string {text:?}: {e}").into())
+}
+
+fn write_txn(txn: &mut Txn, read_only: bool) -> mlua::Result<&mut DirtyRwTxn> {
+    if read_only {
+        return Err(mlua::Error::runtime("the writes are locked or the environment is read-only"));
+    }
+    match txn {
+        Txn::Rw(wtxn) => Ok(wtxn),
+        Txn::Ro(_) | Txn::None => {
+            Err(mlua::Error::runtime("start writing to be able to modify the database"))
+        }
+    }
+}
+
+/// Pushes the next [`LUA_ITER_CHUNK_SIZE`] entries starting with the prefix after the given
+/// key and returns whether there are more entries to read.
+fn read_chunk(
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    prefix: &[u8],
+    after: Option<&[u8]>,
+    entries: &mut Vec<(Vec<u8>, Vec<u8>)>,
+) -> heed::Result<bool> {
+    let iter = match after {
+        // The range skips the duplicates of the last key, the previous chunk ended with them.
+        Some(after) => Box::new(database.range(rtxn, &(Bound::Excluded(after), Bound::Unbounded))?),
+        None => prefix_entries(database, rtxn, prefix)?,
+    };
+
+    for result in iter {
+        let (key, value) = result?;
+        if !key.starts_with(prefix) {
+            break;
+        }
+        if entries.len() >= LUA_ITER_CHUNK_SIZE
+            && entries.last().is_some_and(|(last, _)| last != key)
+        {
+            return Ok(true);
+        }
+        entries.push((key.to_vec(), value.to_vec()));
+    }
+    Ok(false)
+}
+
+fn lua_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string_lossy(),
+        value => value.to_string().unwrap_or_else(|_| format!("{value:?}")),
+    }
+}
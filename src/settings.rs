@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::display_mode::DisplayMode;
+use crate::theme::Theme;
+
+/// The settings that are restored from one session to the other.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The last inner size of the window.
+    pub window_size: Option<[f32; 2]>,
+    /// The last outer position of the window.
+    pub window_position: Option<[f32; 2]>,
+    /// The widths of the keys and values columns, by database name.
+    pub column_widths: HashMap<Option<String>, [f32; 2]>,
+    /// The recently opened environments, the most recent first.
+    pub recent_environments: Vec<PathBuf>,
+    /// The number of recently opened environments to remember.
+    pub max_recent_environments: usize,
+    /// The used map ratio above which a warning is displayed, between 0 and 1.
+    pub map_full_warning: f64,
+    /// The database tabs that were opened, by environment path.
+    pub sessions: HashMap<PathBuf, Vec<SavedPane>>,
+    /// Whether the write transactions with uncommitted changes are aborted without asking.
+    pub skip_abort_confirmation: bool,
+    /// Whether the side panel listing the databases is expanded.
+    pub show_sidebar: bool,
+    /// The size of the body text, the whole interface is scaled accordingly.
+    pub font_size: f32,
+    pub theme: Theme,
+    /// The number of bytes of a value displayed in the table, the rest is elided.
+    pub max_value_display_bytes: usize,
+    /// The pinned keys by environment path.
+    #[serde(with = "base64_bookmarks")]
+    pub bookmarks: HashMap<PathBuf, Bookmarks>,
+    /// The options the environments were last opened with, by environment path.
+    pub env_options: HashMap<PathBuf, EnvOptions>,
+}
+
+/// The options given to LMDB when opening an environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvOptions {
+    /// The size of the memory map, LMDB grows it to the size of the data file if smaller.
+    pub map_size: usize,
+    pub max_dbs: u32,
+    pub max_readers: u32,
+}
+
+impl Default for EnvOptions {
+    /// The LMDB defaults, except for the number of databases.
+    fn default() -> EnvOptions {
+        EnvOptions { map_size: 10 * 1024 * 1024, max_dbs: 1000, max_readers: 126 }
+    }
+}
+
+/// The pinned keys of an environment by database name.
+pub type Bookmarks = BTreeMap<Option<String>, BTreeSet<Vec<u8>>>;
+
+/// (De)serializes the pinned keys as base64 strings rather than arrays of numbers.
+mod base64_bookmarks {
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Bookmarks;
+
+    /// A key saved by this module, or by the versions saving the raw bytes.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SavedKey {
+        Base64(String),
+        Bytes(Vec<u8>),
+    }
+
+    pub fn serialize<S: Serializer>(
+        bookmarks: &HashMap<PathBuf, Bookmarks>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let encoded: HashMap<_, BTreeMap<_, Vec<_>>> = bookmarks
+            .iter()
+            .map(|(path, databases)| {
+                let databases = databases
+                    .iter()
+                    .map(|(name, keys)| {
+                        (name, keys.iter().map(|k| BASE64_STANDARD.encode(k)).collect())
+                    })
+                    .collect();
+                (path, databases)
+            })
+            .collect();
+        encoded.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<PathBuf, Bookmarks>, D::Error> {
+        let saved =
+            HashMap::<PathBuf, BTreeMap<Option<String>, Vec<SavedKey>>>::deserialize(deserializer)?;
+        let mut bookmarks = HashMap::new();
+        for (path, databases) in saved {
+            let mut decoded = Bookmarks::new();
+            for (name, keys) in databases {
+                let keys = keys
+                    .into_iter()
+                    .map(|key| match key {
+                        SavedKey::Base64(key) => BASE64_STANDARD.decode(&key).map_err(|e| {
+                            D::Error::custom(format!("invalid base64 key {key:?}: {e}"))
+                        }),
+                        SavedKey::Bytes(key) => Ok(key),
+                    })
+                    .collect::<Result<_, _>>()?;
+                decoded.insert(name, keys);
+            }
+            bookmarks.insert(path, decoded);
+        }
+        Ok(bookmarks)
+    }
+}
+
+/// A database tab that is reopened on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPane {
+    pub database_name: Option<String>,
+    pub display_mode: DisplayMode,
+    #[serde(default)]
+    pub proto_schema: Option<ProtoSchema>,
+    /// The name given to the tab by the user.
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub use_monospace: bool,
+}
+
+/// The Protobuf message type used to decode the values of a database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtoSchema {
+    /// The `.proto` file declaring the message.
+    pub path: PathBuf,
+    /// The fully qualified name of the message, e.g. `package.Message`.
+    pub message_name: String,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            window_size: None,
+            window_position: None,
+            column_widths: HashMap::new(),
+            recent_environments: Vec::new(),
+            max_recent_environments: 10,
+            map_full_warning: 0.8,
+            sessions: HashMap::new(),
+            skip_abort_confirmation: false,
+            show_sidebar: true,
+            font_size: DEFAULT_FONT_SIZE,
+            theme: Theme::default(),
+            max_value_display_bytes: DEFAULT_MAX_VALUE_DISPLAY_BYTES,
+            bookmarks: HashMap::new(),
+            env_options: HashMap::new(),
+        }
+    }
+}
+
+/// The size of the body text of the default egui style.
+pub const DEFAULT_FONT_SIZE: f32 = 14.0;
+
+/// Long values like JSON blobs would make the rows as wide as the screen.
+const DEFAULT_MAX_VALUE_DISPLAY_BYTES: usize = 256;
+
+impl Settings {
+    const STORAGE_KEY: &'static str = "lmdb-editor-settings";
+
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Settings {
+        storage.and_then(|s| eframe::get_value(s, Self::STORAGE_KEY)).unwrap_or_default()
+    }
+
+    /// Moves the path to the front of the recent environments and forgets the oldest ones.
+    pub fn add_recent_environment(&mut self, path: PathBuf) {
+        self.recent_environments.retain(|p| *p != path);
+        self.recent_environments.insert(0, path);
+        self.recent_environments.truncate(self.max_recent_environments);
+    }
+
+    /// The factor by which the interface is scaled to display the text at the chosen size.
+    pub fn zoom_factor(&self) -> f32 {
+        self.font_size / DEFAULT_FONT_SIZE
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, Self::STORAGE_KEY, self);
+    }
+}
@@ -0,0 +1,172 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use eframe::egui;
+use egui::Color32;
+use heed::{CompactionOption, Env};
+
+use crate::errors::ReportError;
+use crate::task::BackgroundTask;
+use crate::TreeBehavior;
+
+/// The folder of the environment containing the snapshots, one environment folder each.
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// A pane listing the snapshots of the environment, to take, open or delete them.
+#[derive(Default)]
+pub struct Snapshots {
+    /// The snapshot folders, the most recent first, read again after every change.
+    list: Option<io::Result<Vec<PathBuf>>>,
+    /// The copy of the environment into a new snapshot, when running.
+    task: Option<BackgroundTask>,
+    /// The snapshot whose deletion must be confirmed.
+    to_delete: Option<PathBuf>,
+}
+
+impl Snapshots {
+    pub fn ui(&mut self, behavior: &mut TreeBehavior, ui: &mut egui::Ui) {
+        let env = behavior.env;
+        let Snapshots { list, task, to_delete } = self;
+
+        match task {
+            Some(running) => match running.try_finish() {
+                Some(result) => {
+                    if let Some(message) =
+                        result.report(behavior.errors, "Could not take the snapshot")
+                    {
+                        behavior.toasts.add(message);
+                    }
+                    *task = None;
+                    *list = None;
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Copying the environment…");
+                    });
+                    ui.ctx().request_repaint();
+                }
+            },
+            None => {
+                let snapshot = ui.button("Snapshot").on_hover_text(format!(
+                    "Copies the environment into {}",
+                    snapshots_dir(env).display()
+                ));
+                if snapshot.clicked() {
+                    *task = Some(BackgroundTask::spawn("Snapshot", move |_| {
+                        let folder = take_snapshot(env)?;
+                        Ok(format!("Saved a snapshot in {}", folder.display()))
+                    }));
+                }
+            }
+        }
+        ui.separator();
+
+        match list.get_or_insert_with(|| list_snapshots(env)) {
+            Ok(snapshots) if snapshots.is_empty() => {
+                ui.label("No snapshot of this environment yet.");
+            }
+            Ok(snapshots) => {
+                egui::Grid::new("snapshots").striped(true).num_columns(3).show(ui, |ui| {
+                    for folder in snapshots.iter() {
+                        let name = folder.file_name().unwrap_or_default().to_string_lossy();
+                        ui.label(name);
+                        let restore =
+                            ui.button("Restore").on_hover_text("Opens it read-only in a new tab");
+                        if restore.clicked() {
+                            behavior.snapshot_to_open = Some(folder.clone());
+                        }
+                        if ui.button("Delete").clicked() {
+                            *to_delete = Some(folder.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+            Err(e) => {
+                ui.colored_label(Color32::RED, format!("Could not list the snapshots: {e}"));
+            }
+        }
+
+        if let Some(folder) = to_delete.clone() {
+            let mut open = true;
+            let mut done = false;
+            egui::Window::new("Delete the snapshot?")
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("{} will be removed from the disk.", folder.display()));
+                    if ui.button("Delete").clicked() {
+                        std::fs::remove_dir_all(&folder)
+                            .report(behavior.errors, "Could not delete the snapshot");
+                        *list = None;
+                        done = true;
+                    }
+                });
+            if !open || done {
+                *to_delete = None;
+            }
+        }
+    }
+}
+
+fn snapshots_dir(env: &Env) -> PathBuf {
+    env.path().join(SNAPSHOTS_DIR)
+}
+
+/// Returns the snapshot folders of the environment, the most recent first.
+fn list_snapshots(env: &Env) -> io::Result<Vec<PathBuf>> {
+    let mut snapshots = match std::fs::read_dir(snapshots_dir(env)) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter(|path| path.as_ref().map_or(true, |path| path.join("data.mdb").is_file()))
+            .collect::<io::Result<Vec<_>>>()?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    // The timestamps sort lexicographically.
+    snapshots.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(snapshots)
+}
+
+/// Copies the environment into a new `snapshot_<timestamp>` folder and returns it.
+fn take_snapshot(env: &Env) -> anyhow::Result<PathBuf> {
+    let folder = snapshots_dir(env).join(format!("snapshot_{}", timestamp(SystemTime::now())));
+    std::fs::create_dir_all(&folder)
+        .with_context(|| format!("while creating {}", folder.display()))?;
+    // The copy is done in a read transaction, the environment stays usable.
+    let path = folder.join("data.mdb");
+    env.copy_to_file(&path, CompactionOption::Disabled)
+        .with_context(|| format!("while writing {}", path.display()))?;
+    Ok(folder)
+}
+
+/// Formats the time as a basic ISO 8601 UTC timestamp, e.g. `20240131T235959Z`.
+///
+/// The basic format has no colon, which is forbidden in the file names on Windows.
+fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    let (hour, minute, second) = (secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60);
+    format!("{year:04}{month:02}{day:02}T{hour:02}{minute:02}{second:02}Z")
+}
+
+/// Converts a number of days since 1970-01-01 into a proleptic Gregorian date.
+///
+/// This is the `civil_from_days` algorithm of Howard Hinnant.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
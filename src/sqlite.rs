@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use heed::types::Bytes;
+use heed::{Database, RoTxn, RwTxn};
+
+/// Writes every entry of the database as a `key BLOB, value BLOB` row of a table named
+/// after the database, in a new SQLite database, and returns the number of entries.
+///
+/// A generated `json` column exposes the values that are valid JSON to the SQL JSON functions.
+pub fn export_sqlite(
+    database: Database<Bytes, Bytes>,
+    rtxn: &RoTxn,
+    table: &str,
+    path: &Path,
+    progress: &AtomicUsize,
+) -> anyhow::Result<usize> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut connection = rusqlite::Connection::open(path)?;
+    let transaction = connection.transaction()?;
+
+    let table = quote_identifier(table);
+    transaction.execute(
+        &format!(
+            "CREATE TABLE {table} (
+                key BLOB NOT NULL,
+                value BLOB NOT NULL,
+                json TEXT GENERATED ALWAYS AS (
+                    CASE WHEN json_valid(CAST(value AS TEXT)) THEN CAST(value AS TEXT) END
+                ) VIRTUAL
+            )"
+        ),
+        (),
+    )?;
+
+    let mut count = 0;
+    {
+        let mut insert =
+            transaction.prepare(&format!("INSERT INTO {table} (key, value) VALUES (?1, ?2)"))?;
+        for result in database.iter(rtxn)? {
+            let (key, value) = result?;
+            insert.execute((key, value))?;
+            count += 1;
+            progress.store(count, Ordering::Relaxed);
+        }
+    }
+    transaction.commit()?;
+
+    Ok(count)
+}
+
+/// Quotes the name to use it as an SQL identifier, whatever the characters it contains.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// The state of the window choosing the SQLite table and columns to import.
+pub struct SqliteImport {
+    pub path: PathBuf,
+    /// The tables of the SQLite database along with their columns.
+    pub tables: Vec<(String, Vec<String>)>,
+    /// The index of the chosen table.
+    pub table: usize,
+    pub key_column: String,
+    pub value_column: String,
+}
+
+impl SqliteImport {
+    /// Lists the tables of the SQLite database, the key and value columns are preselected.
+    pub fn open(path: PathBuf) -> rusqlite::Result<SqliteImport> {
+        let connection = rusqlite::Connection::open(&path)?;
+        let mut statement = connection.prepare(
+            "SELECT name FROM sqlite_schema WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )?;
+        let names: Vec<String> =
+            statement.query_map((), |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let mut statement =
+                connection.prepare("SELECT name FROM pragma_table_xinfo(?1) WHERE hidden != 1")?;
+            let columns =
+                statement.query_map([&name], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+            tables.push((name, columns));
+        }
+
+        let mut import = SqliteImport {
+            path,
+            tables,
+            table: 0,
+            key_column: String::new(),
+            value_column: String::new(),
+        };
+        import.select_table(0);
+        Ok(import)
+    }
+
+    /// Chooses the table and the columns named `key` and `value`, or the first two ones.
+    pub fn select_table(&mut self, index: usize) {
+        self.table = index;
+        let columns = self.tables.get(index).map_or(&[][..], |(_, columns)| columns);
+        let find = |name: &str, fallback: usize| {
+            let column = columns.iter().find(|c| c.eq_ignore_ascii_case(name));
+            column.or(columns.get(fallback)).cloned().unwrap_or_default()
+        };
+        self.key_column = find("key", 0);
+        self.value_column = find("value", 1);
+    }
+
+    /// Puts the rows of the chosen table into the database and returns the number of entries.
+    ///
+    /// The text and numeric columns are inserted as their textual representation,
+    /// a NULL value as an empty one.
+    pub fn import(
+        &self,
+        database: Database<Bytes, Bytes>,
+        wtxn: &mut RwTxn,
+    ) -> anyhow::Result<usize> {
+        let (table, _) = self.tables.get(self.table).context("no table to import")?;
+        let connection = rusqlite::Connection::open(&self.path)?;
+        let mut statement = connection.prepare(&format!(
+            "SELECT CAST({} AS BLOB), CAST({} AS BLOB) FROM {}",
+            quote_identifier(&self.key_column),
+            quote_identifier(&self.value_column),
+            quote_identifier(table),
+        ))?;
+
+        let mut count = 0;
+        let mut rows = statement.query(())?;
+        while let Some(row) = rows.next()? {
+            let key: Option<Vec<u8>> = row.get(0)?;
+            let value: Option<Vec<u8>> = row.get(1)?;
+            let key = key.with_context(|| format!("row {}: the key is NULL", count + 1))?;
+            database
+                .put(wtxn, &key, &value.unwrap_or_default())
+                .context("while putting the imported entries")?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
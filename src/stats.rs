@@ -0,0 +1,114 @@
+use heed::types::Bytes;
+use heed::{Database, RoTxn};
+
+/// The number of entries after which we stop iterating over the database.
+pub const MAX_ENTRIES: usize = 10_000_000;
+
+/// The number of value bytes after which we stop counting them for the entropy.
+pub const ENTROPY_SAMPLE_BYTES: usize = 10 * 1024 * 1024;
+
+/// The inclusive upper bounds of the length histogram buckets, the last bucket has none.
+const HISTOGRAM_BOUNDS: [usize; 8] = [0, 4, 8, 16, 32, 64, 128, 256];
+
+/// The number of buckets of the length histograms.
+pub const HISTOGRAM_BUCKETS: usize = HISTOGRAM_BOUNDS.len() + 1;
+
+/// Statistics about the keys and values of a database.
+#[derive(Debug, Default)]
+pub struct DatabaseStats {
+    pub entries: usize,
+    pub total_key_bytes: usize,
+    pub total_value_bytes: usize,
+    pub min_key_len: usize,
+    pub max_key_len: usize,
+    pub min_value_len: usize,
+    pub max_value_len: usize,
+    /// The number of keys by length range, see [`histogram_label`].
+    pub key_len_histogram: [usize; HISTOGRAM_BUCKETS],
+    pub value_len_histogram: [usize; HISTOGRAM_BUCKETS],
+    /// The Shannon entropy of the first `ENTROPY_SAMPLE_BYTES` value bytes, in bits per byte.
+    pub value_entropy: f64,
+    /// Whether we stopped iterating after `MAX_ENTRIES` entries.
+    pub truncated: bool,
+}
+
+impl DatabaseStats {
+    pub fn compute(database: Database<Bytes, Bytes>, rtxn: &RoTxn) -> heed::Result<DatabaseStats> {
+        let mut stats = DatabaseStats {
+            min_key_len: usize::MAX,
+            min_value_len: usize::MAX,
+            ..Default::default()
+        };
+        let mut byte_counts = [0usize; 256];
+        let mut sampled_bytes = 0;
+
+        for result in database.iter(rtxn)? {
+            if stats.entries == MAX_ENTRIES {
+                stats.truncated = true;
+                break;
+            }
+
+            let (key, value) = result?;
+            stats.entries += 1;
+            stats.total_key_bytes += key.len();
+            stats.total_value_bytes += value.len();
+            stats.min_key_len = stats.min_key_len.min(key.len());
+            stats.max_key_len = stats.max_key_len.max(key.len());
+            stats.min_value_len = stats.min_value_len.min(value.len());
+            stats.max_value_len = stats.max_value_len.max(value.len());
+            stats.key_len_histogram[histogram_bucket(key.len())] += 1;
+            stats.value_len_histogram[histogram_bucket(value.len())] += 1;
+
+            let sample = &value[..value.len().min(ENTROPY_SAMPLE_BYTES - sampled_bytes)];
+            sample.iter().for_each(|&byte| byte_counts[byte as usize] += 1);
+            sampled_bytes += sample.len();
+        }
+
+        if sampled_bytes != 0 {
+            stats.value_entropy = byte_counts
+                .iter()
+                .filter(|&&count| count != 0)
+                .map(|&count| {
+                    let p = count as f64 / sampled_bytes as f64;
+                    -p * p.log2()
+                })
+                .sum();
+        }
+
+        if stats.entries == 0 {
+            stats.min_key_len = 0;
+            stats.min_value_len = 0;
+        }
+
+        Ok(stats)
+    }
+
+    pub fn average_key_len(&self) -> f64 {
+        average(self.total_key_bytes, self.entries)
+    }
+
+    pub fn average_value_len(&self) -> f64 {
+        average(self.total_value_bytes, self.entries)
+    }
+}
+
+fn histogram_bucket(len: usize) -> usize {
+    HISTOGRAM_BOUNDS.iter().position(|&bound| len <= bound).unwrap_or(HISTOGRAM_BOUNDS.len())
+}
+
+/// The range of lengths counted in the bucket, e.g. `5–8`.
+pub fn histogram_label(bucket: usize) -> String {
+    match bucket {
+        0 => "0".to_owned(),
+        _ if bucket == HISTOGRAM_BOUNDS.len() => format!("{}+", HISTOGRAM_BOUNDS[bucket - 1] + 1),
+        _ => format!("{}–{}", HISTOGRAM_BOUNDS[bucket - 1] + 1, HISTOGRAM_BOUNDS[bucket]),
+    }
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// The number of jobs whose thread has not returned yet, they may hold a read transaction.
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Decrements the running counter when the job thread returns or panics.
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A job running in a background thread that reports the number of processed entries.
+pub struct BackgroundTask {
+    pub name: String,
+    progress: Arc<AtomicUsize>,
+    handle: Option<JoinHandle<anyhow::Result<String>>>,
+}
+
+impl BackgroundTask {
+    /// Spawns the job, it returns a message to display once finished.
+    pub fn spawn<F>(name: impl Into<String>, f: F) -> BackgroundTask
+    where
+        F: FnOnce(&AtomicUsize) -> anyhow::Result<String> + Send + 'static,
+    {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let thread_progress = progress.clone();
+        RUNNING.fetch_add(1, Ordering::SeqCst);
+        let handle = thread::spawn(move || {
+            let _guard = RunningGuard;
+            f(&thread_progress)
+        });
+        BackgroundTask { name: name.into(), progress, handle: Some(handle) }
+    }
+
+    /// Whether any job is still running, the map must not be resized meanwhile.
+    pub fn any_running() -> bool {
+        RUNNING.load(Ordering::SeqCst) != 0
+    }
+
+    pub fn progress(&self) -> usize {
+        self.progress.load(Ordering::Relaxed)
+    }
+
+    /// Returns the outcome of the job if it is finished, only once.
+    pub fn try_finish(&mut self) -> Option<anyhow::Result<String>> {
+        if self.handle.as_ref().is_some_and(JoinHandle::is_finished) {
+            let handle = self.handle.take().unwrap();
+            Some(handle.join().unwrap_or_else(|_| Err(anyhow::anyhow!("the task panicked"))))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+/// The colors of the interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// The dark variant of the Solarized palette by Ethan Schoonover.
+    Solarized,
+}
+
+// The Solarized dark palette, see https://ethanschoonover.com/solarized.
+const BASE03: Color32 = Color32::from_rgb(0x00, 0x2b, 0x36);
+const BASE02: Color32 = Color32::from_rgb(0x07, 0x36, 0x42);
+const BASE01: Color32 = Color32::from_rgb(0x58, 0x6e, 0x75);
+const BASE00: Color32 = Color32::from_rgb(0x65, 0x7b, 0x83);
+const BASE0: Color32 = Color32::from_rgb(0x83, 0x94, 0x96);
+const BASE1: Color32 = Color32::from_rgb(0x93, 0xa1, 0xa1);
+const YELLOW: Color32 = Color32::from_rgb(0xb5, 0x89, 0x00);
+const ORANGE: Color32 = Color32::from_rgb(0xcb, 0x4b, 0x16);
+const RED: Color32 = Color32::from_rgb(0xdc, 0x32, 0x2f);
+const BLUE: Color32 = Color32::from_rgb(0x26, 0x8b, 0xd2);
+const CYAN: Color32 = Color32::from_rgb(0x2a, 0xa1, 0x98);
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dark => "🌙 Dark",
+            Theme::Light => "☀ Light",
+            Theme::Solarized => "🌅 Solarized",
+        }
+    }
+
+    /// The theme after this one, to cycle through them with a single button.
+    pub fn next(&self) -> Theme {
+        match self {
+            Theme::Dark => Theme::Light,
+            Theme::Light => Theme::Solarized,
+            Theme::Solarized => Theme::Dark,
+        }
+    }
+
+    pub fn visuals(&self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+            Theme::Solarized => solarized_visuals(),
+        }
+    }
+}
+
+fn solarized_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(BASE0);
+    visuals.hyperlink_color = BLUE;
+    visuals.faint_bg_color = BASE02;
+    visuals.extreme_bg_color = BASE03;
+    visuals.code_bg_color = BASE02;
+    visuals.warn_fg_color = ORANGE;
+    visuals.error_fg_color = RED;
+    visuals.window_fill = BASE03;
+    visuals.panel_fill = BASE03;
+    visuals.window_stroke.color = BASE01;
+    visuals.selection.bg_fill = BLUE.gamma_multiply(0.5);
+    visuals.selection.stroke.color = BASE1;
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_fill = BASE03;
+    widgets.noninteractive.weak_bg_fill = BASE03;
+    widgets.noninteractive.bg_stroke.color = BASE02;
+    widgets.noninteractive.fg_stroke.color = BASE0;
+    widgets.inactive.bg_fill = BASE02;
+    widgets.inactive.weak_bg_fill = BASE02;
+    widgets.inactive.fg_stroke.color = BASE0;
+    widgets.hovered.bg_fill = BASE01;
+    widgets.hovered.weak_bg_fill = BASE01;
+    widgets.hovered.bg_stroke.color = CYAN;
+    widgets.hovered.fg_stroke.color = BASE1;
+    widgets.active.bg_fill = BASE00;
+    widgets.active.weak_bg_fill = BASE00;
+    widgets.active.bg_stroke.color = YELLOW;
+    widgets.active.fg_stroke.color = BASE1;
+    widgets.open.bg_fill = BASE02;
+    widgets.open.weak_bg_fill = BASE02;
+    widgets.open.fg_stroke.color = BASE1;
+    visuals
+}
@@ -0,0 +1,38 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+/// How long a notification stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Short notifications displayed in the bottom right corner of the window.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<(String, Instant)>,
+}
+
+impl Toasts {
+    pub fn add(&mut self, message: impl Into<String>) {
+        self.toasts.push((message.into(), Instant::now()));
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        self.toasts.retain(|(_, created_at)| created_at.elapsed() < TOAST_DURATION);
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        egui::Area::new("toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -30.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                for (message, _) in &self.toasts {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(message.as_str());
+                    });
+                }
+            });
+
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
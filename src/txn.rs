@@ -1,4 +1,5 @@
 use std::mem;
+use std::ops::{Deref, DerefMut};
 
 use heed::Env;
 
@@ -10,49 +11,127 @@ pub(crate) enum Txn {
     /// A read-only transaction.
     Ro(RoTxn<'static>),
     /// A read-write transaction.
-    Rw(RwTxn<'static>),
+    Rw(DirtyRwTxn),
+    /// No transaction, when a new read transaction could not be opened.
     None,
 }
 
+/// A read-write transaction that remembers whether it has been written to.
+pub(crate) struct DirtyRwTxn {
+    txn: RwTxn<'static>,
+    /// Whether entries have been put or deleted, see [`DirtyRwTxn::mark_dirty`].
+    dirty: bool,
+}
+
+impl DirtyRwTxn {
+    /// Records that entries were put or deleted, to be called once the writes succeeded.
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+}
+
+impl Deref for DirtyRwTxn {
+    type Target = RwTxn<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
+
+impl DerefMut for DirtyRwTxn {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
+    }
+}
+
 impl Txn {
+    /// Returns the current transaction as a read transaction, a read-write one derefs to it.
+    ///
+    /// Must not be called on `Txn::None`, see [`Txn::is_none`].
+    pub(crate) fn read_txn(&self) -> &RoTxn<'static> {
+        match self {
+            Self::Ro(rtxn) => rtxn,
+            Self::Rw(wtxn) => wtxn,
+            Self::None => unreachable!(),
+        }
+    }
+
+    /// Returns `true` when the last read transaction could not be opened, see [`Txn::refresh`].
+    pub(crate) fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    /// Returns `true` if entries have been put or deleted in the current read-write transaction.
+    pub(crate) fn is_dirty(&self) -> bool {
+        matches!(self, Self::Rw(wtxn) if wtxn.dirty)
+    }
+
     /// Commit read-write transaction and change it to read-only. Noop for `Txn::Ro`.
-    pub(crate) fn commit(&mut self, env: &'static Env) {
-        self.end_rw(env, |wtxn| wtxn.commit().unwrap());
+    ///
+    /// The transaction is read-only even when the commit fails, the writes are then lost.
+    pub(crate) fn try_commit(&mut self, env: &'static Env) -> heed::Result<()> {
+        self.end_rw(env, RwTxn::commit)
     }
 
     /// Abort read-write transaction and change it to read-only. Noop for `Txn::Ro`.
-    pub(crate) fn abort(&mut self, env: &'static Env) {
-        self.end_rw(env, |wtxn| wtxn.abort());
+    pub(crate) fn try_abort(&mut self, env: &'static Env) -> heed::Result<()> {
+        self.end_rw(env, |wtxn| {
+            wtxn.abort();
+            Ok(())
+        })
     }
 
-    /// Refresh the current read transaction. Noop fro `Txn::Rw`.
-    pub(crate) fn refresh(&mut self, env: &'static Env) {
+    /// Change the read-only transaction to a read-write one. Noop for `Txn::Rw`.
+    ///
+    /// The transaction stays read-only when the write transaction cannot be opened.
+    pub(crate) fn start_write(&mut self, env: &'static Env) -> heed::Result<()> {
         if matches!(self, Self::Ro(_)) {
+            *self = Self::Rw(DirtyRwTxn { txn: env.write_txn()?, dirty: false });
+        }
+        Ok(())
+    }
+
+    /// Refresh the current read transaction, or open one for `Txn::None`. Noop fro `Txn::Rw`.
+    ///
+    /// There is no transaction left, `Txn::None`, when the new one cannot be opened.
+    pub(crate) fn refresh(&mut self, env: &'static Env) -> heed::Result<()> {
+        if matches!(self, Self::Ro(_) | Self::None) {
             // We must drop the rtxn before opening a new one as it is forbidden
             // to have two transactions on the same thread at any given time.
             let rtxn = mem::replace(self, Self::None);
             drop(rtxn);
-            *self = Self::Ro(env.read_txn().unwrap());
+            *self = Self::Ro(env.read_txn()?);
         }
+        Ok(())
     }
 
-    pub(crate) fn end_rw(&mut self, env: &'static Env, f: fn(RwTxn<'static>)) {
+    /// Ends the read-write transaction with `f` and opens a new read transaction.
+    ///
+    /// The error of `f` is returned first, there is no transaction left, `Txn::None`,
+    /// when the read transaction cannot be opened.
+    pub(crate) fn end_rw(
+        &mut self,
+        env: &'static Env,
+        f: fn(RwTxn<'static>) -> heed::Result<()>,
+    ) -> heed::Result<()> {
         match self {
-            Self::Ro(_) => (),
+            Self::Ro(_) => Ok(()),
             Self::None => unreachable!(),
             Self::Rw(_) => {
                 // We should call `f` (which commits or aborts the read-write
                 // transaction) before creating a new read-only transaction,
                 // otherwise the read-only transaction will not see the changes
                 // made by the read-write transaction.
-                match mem::replace(self, Self::None) {
-                    Self::Rw(wtxn) => f(wtxn),
+                let result = match mem::replace(self, Self::None) {
+                    Self::Rw(wtxn) => f(wtxn.txn),
                     Self::Ro(_) | Self::None => unreachable!(),
-                }
-                let rtxn = env.read_txn().unwrap();
-                match mem::replace(self, Self::Ro(rtxn)) {
-                    Self::None => (),
-                    Self::Ro(_) | Self::Rw(_) => unreachable!(),
+                };
+                match env.read_txn() {
+                    Ok(rtxn) => {
+                        *self = Self::Ro(rtxn);
+                        result
+                    }
+                    Err(e) => result.and(Err(e)),
                 }
             }
         }
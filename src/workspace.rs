@@ -0,0 +1,973 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use eframe::egui;
+use egui::Color32;
+use egui_tiles::{Container, Tile, TileId};
+use heed::types::{Bytes, DecodeIgnore, Str};
+use heed::{Env, EnvFlags, EnvOpenOptions, RoTxn};
+
+use crate::byte_size;
+use crate::database_entries::{self, DatabaseEntries};
+use crate::errors::{AppError, ReportError};
+use crate::history::History;
+use crate::integrity::IntegrityReport;
+use crate::settings::{Bookmarks, EnvOptions, SavedPane, Settings};
+use crate::task::BackgroundTask;
+use crate::toasts::Toasts;
+use crate::txn::Txn;
+use crate::{database_list_ui, Pane, TreeBehavior, READ_ONLY_HINT};
+
+const COMMIT_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
+// Ctrl+Z is left to the undo, aborting everything must be harder to press by mistake.
+const ABORT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Backspace,
+);
+pub const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+pub const REDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+
+/// LMDB requires a map size that is a multiple of the OS page size, we use the largest one.
+const MAP_SIZE_ALIGNMENT: usize = 64 * 1024;
+
+/// The used map ratio above which the map full warning becomes red.
+const MAP_FULL_DANGER: f64 = 0.95;
+
+/// The environments opened during this session.
+///
+/// Transactions must borrow their environment for `'static`, we therefore never close them
+/// and give the same environment back when the user opens it again.
+static OPENED_ENVS: Mutex<Vec<&'static Env>> = Mutex::new(Vec::new());
+
+/// Whether the process cannot write the data file of the environment, or create it.
+fn lacks_write_permission(path: &Path) -> bool {
+    let data_file = path.join("data.mdb");
+    if data_file.exists() {
+        let opened = OpenOptions::new().write(true).open(&data_file);
+        opened.is_err_and(|e| e.kind() == io::ErrorKind::PermissionDenied)
+    } else {
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly())
+    }
+}
+
+/// The magic number starting the meta pages of an LMDB data file.
+const MDB_MAGIC: u32 = 0xBEEF_C0DE;
+
+/// Reads the map size stored in the meta pages of the `data.mdb` file of the environment.
+///
+/// LMDB alternates between two meta pages, the current one has the largest transaction id.
+/// The offsets are the ones of the `MDB_meta` struct on 64-bit platforms, after the 16 bytes
+/// page header: the map size at 32, the page size in the free database record at 40 and
+/// the transaction id at 144. Returns `None` when the file is missing or is not LMDB.
+pub fn detect_env_map_size(path: &Path) -> Option<usize> {
+    fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+        Some(u32::from_ne_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+    }
+    fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+        Some(u64::from_ne_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?))
+    }
+    /// Returns the transaction id and the map size of the meta page.
+    fn read_meta(page: &[u8]) -> Option<(u64, u64)> {
+        if read_u32(page, 16)? != MDB_MAGIC {
+            return None;
+        }
+        Some((read_u64(page, 144)?, read_u64(page, 32)?))
+    }
+
+    let mut file = File::open(path.join("data.mdb")).ok()?;
+    let mut first_page = [0; 152];
+    file.read_exact(&mut first_page).ok()?;
+    let page_size = read_u32(&first_page, 40)? as usize;
+    let first_meta = read_meta(&first_page)?;
+
+    let mut second_page = [0; 152];
+    file.seek(SeekFrom::Start(page_size as u64)).ok()?;
+    let second_meta = file.read_exact(&mut second_page).ok().and_then(|()| read_meta(&second_page));
+
+    let (_, map_size) = second_meta.map_or(first_meta, |second| first_meta.max(second));
+    usize::try_from(map_size).ok()
+}
+
+/// Opens the LMDB environment at the given path, or returns it if it is already opened.
+///
+/// The options are only used the first time, LMDB cannot change them once opened.
+pub fn open_env(
+    path: &Path,
+    read_only: bool,
+    options: &EnvOptions,
+) -> anyhow::Result<&'static Env> {
+    if !path.exists() {
+        anyhow::bail!("{} does not exist", path.display());
+    }
+    if !path.is_dir() {
+        anyhow::bail!("{} is not an LMDB environment directory", path.display());
+    }
+
+    let mut opened = OPENED_ENVS.lock().unwrap();
+    let canonical = path.canonicalize()?;
+    if let Some(env) = opened.iter().find(|env| env.path() == canonical) {
+        return Ok(env);
+    }
+
+    let mut env_options = EnvOpenOptions::new();
+    env_options
+        .map_size(options.map_size)
+        .max_dbs(options.max_dbs)
+        .max_readers(options.max_readers);
+    if read_only {
+        unsafe { env_options.flags(EnvFlags::READ_ONLY) };
+    }
+    let env = unsafe { env_options.open(path) }
+        .with_context(|| format!("{} is not a valid LMDB environment", path.display()))?;
+
+    let env = Box::leak(Box::new(env));
+    opened.push(env);
+    Ok(env)
+}
+
+/// An opened environment along with its transaction and tabs.
+pub struct Workspace {
+    pub env: &'static Env,
+    /// Whether this environment was opened read-only, whatever the command line flag.
+    read_only: bool,
+    txn: Txn,
+    tree: egui_tiles::Tree<Pane>,
+    database_names: Vec<String>,
+    /// The number of entries of the main database when the names were listed,
+    /// they are only listed again when it changes or on an explicit refresh.
+    main_db_len: u64,
+    env_status: EnvStatus,
+    /// Incremented every time the transaction is committed, aborted or refreshed.
+    txn_generation: u64,
+    /// The mutations done in the current write transaction.
+    history: History,
+    /// The pinned keys, saved in the settings when the application closes.
+    pub bookmarks: Bookmarks,
+    /// The running integrity check, it stores its report before finishing.
+    integrity_check: Option<(BackgroundTask, Arc<Mutex<Option<IntegrityReport>>>)>,
+    /// The result of the last integrity check, displayed until the window is closed.
+    integrity_report: Option<anyhow::Result<IntegrityReport>>,
+    /// The new map size typed in the resize window, when opened.
+    resize_map: Option<String>,
+    /// Whether the mutations are disabled, even during a write transaction.
+    write_lock: bool,
+    /// The database tab being renamed and the alias typed so far.
+    renaming_tab: Option<(TileId, String)>,
+    /// The snapshot folder a pane asked to open, taken by the application.
+    pub snapshot_to_open: Option<PathBuf>,
+    /// Whether the abort confirmation window is shown.
+    confirm_abort: bool,
+    /// Whether the read transaction is periodically renewed to see the external writes.
+    auto_refresh: bool,
+    /// The number of seconds between two automatic refreshes.
+    auto_refresh_interval: u64,
+    last_refresh: Instant,
+}
+
+impl Workspace {
+    /// Opens the environment and a tab on its unnamed main database, only when `open_main_db`
+    /// is set or when the environment has no named database to browse instead.
+    pub fn open(
+        path: &Path,
+        read_only: bool,
+        open_main_db: bool,
+        options: &EnvOptions,
+    ) -> anyhow::Result<Workspace> {
+        // Opening a write transaction without the permission would fail with EACCES.
+        let read_only = read_only || lacks_write_permission(path);
+        let env = open_env(path, read_only, options)?;
+
+        let rtxn = env.read_txn()?;
+        let database_names = list_database_names(env, &rtxn)?;
+        rtxn.commit()?;
+
+        let main_db = if open_main_db || database_names.is_empty() {
+            if read_only {
+                let rtxn = env.read_txn()?;
+                let main_db = env.open_database(&rtxn, None)?.context("missing main database")?;
+                rtxn.commit()?;
+                Some(main_db)
+            } else {
+                let mut wtxn = env.write_txn()?;
+                let main_db = env.create_database(&mut wtxn, None)?;
+                wtxn.commit()?;
+                Some(main_db)
+            }
+        } else {
+            None
+        };
+
+        let rtxn = env.read_txn()?;
+        let mut tiles = egui_tiles::Tiles::default();
+        let mut tabs = Vec::new();
+        if let Some(main_db) = main_db {
+            tabs.push(tiles.insert_pane(Pane::database_entries(env, &rtxn, None, main_db)));
+        }
+        tabs.push(tiles.insert_pane(Pane::OpenNew { database_to_open: String::new() }));
+        let root = tiles.insert_tab_tile(tabs);
+        let tree = egui_tiles::Tree::new(env.path().display().to_string(), root, tiles);
+
+        let main_db_len = main_db_len(env, &rtxn)?;
+        let env_status = EnvStatus::read(env, &rtxn);
+        Ok(Workspace {
+            env,
+            read_only,
+            env_status,
+            txn_generation: 0,
+            history: History::default(),
+            bookmarks: Bookmarks::new(),
+            integrity_check: None,
+            integrity_report: None,
+            resize_map: None,
+            write_lock: false,
+            renaming_tab: None,
+            confirm_abort: false,
+            snapshot_to_open: None,
+            txn: Txn::Ro(rtxn),
+            tree,
+            database_names,
+            main_db_len,
+            auto_refresh: false,
+            auto_refresh_interval: 5,
+            last_refresh: Instant::now(),
+        })
+    }
+
+    /// The name of the environment directory, used as the workspace tab title.
+    pub fn name(&self) -> String {
+        let path = self.env.path();
+        match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => path.display().to_string(),
+        }
+    }
+
+    /// The database tabs to reopen on the next launch, in the order of the layout.
+    pub fn session(&self) -> Vec<SavedPane> {
+        let mut panes = Vec::new();
+        let mut stack: Vec<_> = self.tree.root().into_iter().collect();
+        while let Some(tile_id) = stack.pop() {
+            match self.tree.tiles.get(tile_id) {
+                Some(Tile::Pane(Pane::DatabaseEntries(entries))) => panes.push(SavedPane {
+                    database_name: entries.database_name.clone(),
+                    display_mode: entries.display_mode,
+                    proto_schema: entries.proto_schema.clone(),
+                    alias: entries.alias.clone(),
+                    use_monospace: entries.use_monospace,
+                }),
+                Some(Tile::Container(container)) => {
+                    stack.extend(container.children_vec().into_iter().rev());
+                }
+                _ => (),
+            }
+        }
+        panes
+    }
+
+    /// Replaces the tabs by the ones of a previous session.
+    ///
+    /// The databases that no longer exist are skipped and reported in a toast.
+    pub fn restore_session(&mut self, session: &[SavedPane], toasts: &mut Toasts) {
+        let rtxn = self.txn.read_txn();
+        let mut tiles = egui_tiles::Tiles::default();
+        let mut tabs = Vec::new();
+        for saved in session {
+            match self.env.open_database(rtxn, saved.database_name.as_deref()) {
+                Ok(Some(database)) => {
+                    let name = saved.database_name.clone();
+                    let has_dup_sort =
+                        database_entries::is_dup_sort(self.env, rtxn, name.as_deref());
+                    let mut entries = DatabaseEntries::new(name, database, has_dup_sort);
+                    entries.display_mode = saved.display_mode;
+                    entries.alias = saved.alias.clone();
+                    entries.use_monospace = saved.use_monospace;
+                    if let Some(schema) = saved.proto_schema.clone() {
+                        if let Err(e) = entries.set_proto_schema(schema) {
+                            toasts.add(format!("Could not load the Protobuf schema: {e:#}"));
+                        }
+                    }
+                    let pane = Pane::DatabaseEntries(Box::new(entries));
+                    tabs.push(tiles.insert_pane(pane));
+                }
+                _ => {
+                    let name = saved.database_name.as_deref().unwrap_or("{main}");
+                    toasts.add(format!("The {name} database no longer exists"));
+                }
+            }
+        }
+
+        if !tabs.is_empty() {
+            let root = tiles.insert_tab_tile(tabs);
+            self.tree = egui_tiles::Tree::new(self.env.path().display().to_string(), root, tiles);
+        }
+    }
+
+    /// Reads again the values that only change with the transaction.
+    ///
+    /// The databases are listed again when `list_databases` is set or the main database changed.
+    fn refresh_caches(&mut self, list_databases: bool, errors: &mut VecDeque<AppError>) {
+        if self.txn.is_none() {
+            return;
+        }
+        let rtxn = self.txn.read_txn();
+        let len = main_db_len(self.env, rtxn).report(errors, "Could not read the main database");
+        if list_databases || len != Some(self.main_db_len) {
+            if let Some(names) =
+                list_database_names(self.env, rtxn).report(errors, "Could not list the databases")
+            {
+                self.database_names = names;
+                self.main_db_len = len.unwrap_or_default();
+            }
+        }
+        self.env_status = EnvStatus::read(self.env, self.txn.read_txn());
+        self.txn_generation += 1;
+        // The history is only about the current write transaction.
+        if !matches!(self.txn, Txn::Rw(_)) {
+            self.history.clear();
+        }
+    }
+
+    /// Inserts the pane as a new active tab of the root container.
+    fn open_pane(&mut self, pane: Pane) {
+        let tid = self.tree.tiles.insert_pane(pane);
+        match self.tree.root() {
+            Some(root) => match self.tree.tiles.get_mut(root) {
+                Some(Tile::Container(Container::Tabs(tabs))) => {
+                    tabs.add_child(tid);
+                    tabs.set_active(tid);
+                }
+                _ => {
+                    let tabs = self.tree.tiles.insert_tab_tile(vec![root, tid]);
+                    if let Some(Tile::Container(Container::Tabs(tabs))) =
+                        self.tree.tiles.get_mut(tabs)
+                    {
+                        tabs.set_active(tid);
+                    }
+                    self.tree.root = Some(tabs);
+                }
+            },
+            None => self.tree.root = Some(tid),
+        }
+    }
+
+    /// Inserts the pane next to the given tile, or as a new root tab if it is not in a tab container.
+    fn open_sibling_pane(&mut self, tile_id: TileId, pane: Pane) {
+        let parent = self.tree.tiles.parent_of(tile_id);
+        match parent.and_then(|parent| self.tree.tiles.get(parent)) {
+            Some(Tile::Container(Container::Tabs(_))) => {
+                let tid = self.tree.tiles.insert_pane(pane);
+                if let Some(Tile::Container(Container::Tabs(tabs))) =
+                    self.tree.tiles.get_mut(parent.unwrap())
+                {
+                    tabs.add_child(tid);
+                    tabs.set_active(tid);
+                }
+            }
+            _ => self.open_pane(pane),
+        }
+    }
+
+    /// Displays a copy of the database tab in new tabs to the right of its tabs.
+    fn split_right(&mut self, tile_id: TileId) {
+        let Some(Tile::Pane(Pane::DatabaseEntries(entries))) = self.tree.tiles.get(tile_id) else {
+            return;
+        };
+        let pane =
+            self.tree.tiles.insert_pane(Pane::DatabaseEntries(Box::new(entries.duplicate())));
+        let new_tabs = self.tree.tiles.insert_tab_tile(vec![pane]);
+
+        // The tabs are moved to a new id and replaced by the horizontal container,
+        // this way the parent of the tabs, whatever its kind, now contains the container.
+        let tabs_id = self.tree.tiles.parent_of(tile_id).unwrap_or(tile_id);
+        let Some(tabs) = self.tree.tiles.remove(tabs_id) else { return };
+        let moved_tabs = self.tree.tiles.insert_new(tabs);
+        let container = Container::new_horizontal(vec![moved_tabs, new_tabs]);
+        self.tree.tiles.insert(tabs_id, Tile::Container(container));
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &mut Settings,
+        read_only: bool,
+        toasts: &mut Toasts,
+        errors: &mut VecDeque<AppError>,
+    ) {
+        let env = self.env;
+        let read_only = read_only || self.read_only;
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let EnvStatus { last_txn_id, readers, .. } = self.env_status;
+                ui.label(if matches!(self.txn, Txn::Rw(_)) { "RW" } else { "RO" });
+                ui.separator();
+                ui.label(format!("Last transaction ID: {last_txn_id}"));
+                ui.separator();
+                ui.label(format!("Map used: {:.1}%", self.env_status.fill_ratio() * 100.0));
+                ui.separator();
+                ui.label(format!("Active readers: {readers}"));
+            });
+        });
+
+        if self.read_only {
+            let frame = egui::Frame::none().fill(Color32::from_rgb(255, 200, 0)).inner_margin(4.0);
+            egui::TopBottomPanel::top("read_only_banner").frame(frame).show(ctx, |ui| {
+                ui.colored_label(Color32::BLACK, "Read-only environment").on_hover_text(
+                    "The environment is opened with MDB_RDONLY, \
+                    because of the command line flag or of the file permissions",
+                );
+            });
+        }
+
+        // The tabs need a transaction, the user can retry once a reader slot is freed.
+        if self.txn.is_none() {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.colored_label(Color32::RED, "There is no transaction to read the environment.");
+                if ui.button("Retry").clicked()
+                    && self
+                        .txn
+                        .refresh(env)
+                        .report(errors, "Could not open a read transaction")
+                        .is_some()
+                {
+                    self.refresh_caches(true, errors);
+                }
+            });
+            return;
+        }
+
+        egui::SidePanel::left("database_list").show_animated(ctx, settings.show_sidebar, |ui| {
+            ui.heading("Databases");
+            ui.separator();
+            if let Some(name) = database_list_ui(ui, &self.database_names) {
+                let pane = database_entries_pane(env, self.txn.read_txn(), name)
+                    .report(errors, "Could not open the database");
+                if let Some(pane) = pane.flatten() {
+                    self.open_pane(pane);
+                }
+            }
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let mut txn_changed = false;
+            let mut list_databases = false;
+            ui.horizontal(|ui| {
+                let button = if matches!(self.txn, Txn::Rw(_)) {
+                    egui::Button::new("currently writing").fill(Color32::GREEN)
+                } else {
+                    egui::Button::new("start writing")
+                };
+
+                let response =
+                    ui.add_enabled(!read_only, button).on_disabled_hover_text(READ_ONLY_HINT);
+                if response.clicked() {
+                    self.txn.start_write(env).report(errors, "Could not start writing");
+                }
+
+                let lock = if self.write_lock {
+                    egui::Button::new(egui::RichText::new("🔒 locked").color(Color32::WHITE))
+                        .fill(Color32::RED)
+                } else {
+                    egui::Button::new("🔓")
+                };
+                let lock = ui.add_enabled(!read_only, lock).on_hover_text(if self.write_lock {
+                    "The writes are locked, click to allow them again"
+                } else {
+                    "Lock the writes to inspect the entries without modifying them"
+                });
+                if lock.clicked() {
+                    self.write_lock = !self.write_lock;
+                }
+
+                // The shortcuts are left to the text fields being edited, Ctrl+Z undoes their text.
+                let shortcuts = ctx.memory(|m| m.focus().is_none());
+                let pressed =
+                    |shortcut| shortcuts && ctx.input_mut(|i| i.consume_shortcut(shortcut));
+
+                if matches!(self.txn, Txn::Rw(_)) {
+                    let label = match self.history.pending_mutations() {
+                        0 => "commit changes".to_owned(),
+                        count => format!("commit changes ({count})"),
+                    };
+                    let commit =
+                        ui.button(label).on_hover_text(ctx.format_shortcut(&COMMIT_SHORTCUT));
+                    if commit.clicked() || pressed(&COMMIT_SHORTCUT) {
+                        self.txn.try_commit(env).report(errors, "Could not commit the changes");
+                        txn_changed = true;
+                    }
+
+                    let abort = ui
+                        .button("abort changes")
+                        .on_hover_text(ctx.format_shortcut(&ABORT_SHORTCUT));
+                    if abort.clicked() || pressed(&ABORT_SHORTCUT) {
+                        if !self.txn.is_dirty() || settings.skip_abort_confirmation {
+                            self.txn.try_abort(env).report(errors, "Could not abort the changes");
+                            txn_changed = true;
+                        } else {
+                            self.confirm_abort = true;
+                        }
+                    }
+
+                    if let (Txn::Rw(wtxn), false) = (&mut self.txn, self.write_lock) {
+                        if pressed(&UNDO_SHORTCUT) {
+                            self.history.undo(wtxn).report(errors, "Could not undo the change");
+                        }
+                        if pressed(&REDO_SHORTCUT) {
+                            self.history.redo(wtxn).report(errors, "Could not redo the change");
+                        }
+                    }
+                } else if ui.button("refresh").clicked() {
+                    self.txn.refresh(env).report(errors, "Could not refresh the transaction");
+                    txn_changed = true;
+                    list_databases = true;
+                }
+
+                if ui.button("list databases").clicked() {
+                    self.open_pane(Pane::DatabaseList);
+                }
+
+                if ui.button("Env Info").clicked() {
+                    self.open_pane(Pane::EnvInfo { compaction: None, backup: None });
+                }
+
+                if ui.button("Find and replace").clicked() {
+                    self.open_pane(Pane::FindReplace(Box::default()));
+                }
+
+                if ui.button("Transaction log").clicked() {
+                    self.open_pane(Pane::TransactionLog);
+                }
+
+                if ui.button("Bookmarks").clicked() {
+                    self.open_pane(Pane::Bookmarks);
+                }
+
+                if ui.button("Snapshots").clicked() {
+                    self.open_pane(Pane::Snapshots(Box::default()));
+                }
+
+                let check = ui
+                    .add_enabled(
+                        self.integrity_check.is_none(),
+                        egui::Button::new("Check integrity"),
+                    )
+                    .on_hover_text("Iterates over the entries of every database")
+                    .on_disabled_hover_text("The integrity is being checked");
+                if check.clicked() {
+                    let report = Arc::new(Mutex::new(None));
+                    let task_report = report.clone();
+                    let task = BackgroundTask::spawn("Checking", move |progress| {
+                        let rtxn = env.read_txn()?;
+                        let report = IntegrityReport::check(env, &rtxn, progress)?;
+                        let message = format!("Checked {} databases", report.databases.len());
+                        *task_report.lock().unwrap() = Some(report);
+                        Ok(message)
+                    });
+                    self.integrity_report = None;
+                    self.integrity_check = Some((task, report));
+                }
+
+                let resize = ui
+                    .add_enabled(!read_only, egui::Button::new("Resize map"))
+                    .on_disabled_hover_text(READ_ONLY_HINT);
+                if resize.clicked() {
+                    self.resize_map = Some(byte_size::format(self.env_status.map_size));
+                }
+
+                ui.separator();
+                if ui.checkbox(&mut self.auto_refresh, "Auto-refresh").changed() {
+                    self.last_refresh = Instant::now();
+                }
+                ui.add_enabled(
+                    self.auto_refresh,
+                    egui::DragValue::new(&mut self.auto_refresh_interval)
+                        .clamp_range(1..=60)
+                        .suffix(" s"),
+                );
+            });
+
+            let fill_ratio = self.env_status.fill_ratio();
+            if fill_ratio >= settings.map_full_warning {
+                let color =
+                    if fill_ratio >= MAP_FULL_DANGER { Color32::RED } else { Color32::YELLOW };
+                ui.colored_label(
+                    color,
+                    format!(
+                        "Environment is {:.0}% full. Consider increasing the map size.",
+                        fill_ratio * 100.0
+                    ),
+                );
+            }
+
+            if let Some((task, report)) = &mut self.integrity_check {
+                match task.try_finish() {
+                    Some(Ok(_)) => {
+                        let report = report.lock().unwrap().take();
+                        self.integrity_report = report.map(Ok);
+                        self.integrity_check = None;
+                    }
+                    Some(Err(e)) => {
+                        self.integrity_report = Some(Err(e));
+                        self.integrity_check = None;
+                    }
+                    None => ctx.request_repaint(),
+                }
+            }
+
+            if self.integrity_check.is_some() || self.integrity_report.is_some() {
+                let mut open = true;
+                egui::Window::new("Integrity check")
+                    .collapsible(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .open(&mut open)
+                    .show(ctx, |ui| match (&self.integrity_check, &self.integrity_report) {
+                        (Some((task, _)), _) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!("{}: {} entries", task.name, task.progress()));
+                            });
+                        }
+                        (None, Some(Ok(report))) => integrity_report_ui(ui, report),
+                        (None, Some(Err(e))) => {
+                            ui.colored_label(Color32::RED, format!("The check failed: {e:#}"));
+                        }
+                        (None, None) => (),
+                    });
+                // Closing the window while checking detaches the task and drops its report.
+                if !open {
+                    self.integrity_check = None;
+                    self.integrity_report = None;
+                }
+            }
+
+            if self.confirm_abort {
+                let mut open = true;
+                let mut done = false;
+                egui::Window::new("Abort the write transaction")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        ui.colored_label(
+                            Color32::RED,
+                            format!(
+                                "Discard {} uncommitted changes?",
+                                self.history.pending_mutations()
+                            ),
+                        );
+                        ui.checkbox(&mut settings.skip_abort_confirmation, "Do not ask again");
+                        ui.horizontal(|ui| {
+                            if ui.button("Yes").clicked() {
+                                self.txn
+                                    .try_abort(env)
+                                    .report(errors, "Could not abort the changes");
+                                txn_changed = true;
+                                done = true;
+                            }
+                            if ui.button("No").clicked() {
+                                done = true;
+                            }
+                        });
+                    });
+                // The transaction can also end from another pane while we ask.
+                if !open || done || !matches!(self.txn, Txn::Rw(_)) {
+                    self.confirm_abort = false;
+                }
+            }
+
+            if let Some(new_size) = &mut self.resize_map {
+                let mut open = true;
+                let mut resized = None;
+                egui::Window::new("Resize the map")
+                    .collapsible(false)
+                    .resizable(false)
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "Current map size: {}",
+                            byte_size::format(self.env_status.map_size)
+                        ));
+                        let parsed = byte_size::parse(new_size).filter(|&size| size != 0);
+                        let response = ui.add(
+                            egui::TextEdit::singleline(new_size).hint_text("new size, e.g. 10 GB"),
+                        );
+                        if parsed.is_none() {
+                            ui.painter().rect_stroke(
+                                response.rect,
+                                2.0,
+                                egui::Stroke::new(1.0, Color32::RED),
+                            );
+                        }
+
+                        let writing = matches!(self.txn, Txn::Rw(_));
+                        let busy = BackgroundTask::any_running();
+                        if busy {
+                            ctx.request_repaint_after(Duration::from_millis(200));
+                        }
+                        let button = ui
+                            .add_enabled(
+                                parsed.is_some() && !writing && !busy,
+                                egui::Button::new("resize"),
+                            )
+                            .on_disabled_hover_text(
+                                "Enter a valid size, commit or abort the write transaction \
+                                 and wait for the background tasks to finish",
+                            );
+                        if button.clicked() {
+                            resized = parsed;
+                        }
+                    });
+
+                if let Some(size) = resized {
+                    let old_size = self.env_status.map_size;
+                    let size = size.next_multiple_of(MAP_SIZE_ALIGNMENT);
+                    // The map can only be resized when no transaction is alive.
+                    self.txn = Txn::None;
+                    match unsafe { env.resize(size) } {
+                        Ok(()) => toasts.add(format!(
+                            "Resized the map from {} to {}",
+                            byte_size::format(old_size),
+                            byte_size::format(size)
+                        )),
+                        Err(e) => toasts.add(format!("Could not resize the map: {e}")),
+                    }
+                    self.txn.refresh(env).report(errors, "Could not open a read transaction");
+                    txn_changed = true;
+                }
+                if !open || resized.is_some() {
+                    self.resize_map = None;
+                }
+            }
+
+            if self.auto_refresh && matches!(self.txn, Txn::Ro(_)) {
+                let interval = Duration::from_secs(self.auto_refresh_interval);
+                let elapsed = self.last_refresh.elapsed();
+                if elapsed >= interval {
+                    self.txn.refresh(env).report(errors, "Could not refresh the transaction");
+                    self.last_refresh = Instant::now();
+                    txn_changed = true;
+                    ctx.request_repaint_after(interval);
+                } else {
+                    ctx.request_repaint_after(interval - elapsed);
+                }
+            }
+
+            if txn_changed {
+                self.refresh_caches(list_databases, errors);
+            }
+            if self.txn.is_none() {
+                ctx.request_repaint();
+                return;
+            }
+
+            let Workspace {
+                env,
+                ref mut txn,
+                tree,
+                database_names,
+                txn_generation,
+                history,
+                bookmarks,
+                renaming_tab,
+                ..
+            } = self;
+
+            let mut open_databases: Vec<_> = tree
+                .tiles
+                .tiles()
+                .filter_map(|tile| match tile {
+                    Tile::Pane(Pane::DatabaseEntries(entries)) => {
+                        Some((entries.database_name.clone(), entries.database))
+                    }
+                    _ => None,
+                })
+                .collect();
+            open_databases.sort_by(|(a, _), (b, _)| a.cmp(b));
+            open_databases.dedup_by(|(a, _), (b, _)| a == b);
+
+            let mut behavior = TreeBehavior {
+                env,
+                txn,
+                txn_generation: *txn_generation,
+                history,
+                bookmarks,
+                database_names,
+                open_databases: &open_databases,
+                settings,
+                read_only: read_only || self.write_lock,
+                toasts,
+                errors,
+                renaming_tab,
+                tab_alias: None,
+                tile_to_split: None,
+                pane_to_open: None,
+                snapshot_to_open: None,
+                txn_changed: false,
+            };
+            tree.ui(&mut behavior, ui);
+
+            let TreeBehavior {
+                pane_to_open,
+                txn_changed,
+                tab_alias,
+                tile_to_split,
+                snapshot_to_open,
+                ..
+            } = behavior;
+            self.snapshot_to_open = snapshot_to_open;
+            if txn_changed {
+                self.refresh_caches(false, errors);
+            }
+            if let Some((tile_id, alias)) = tab_alias {
+                if let Some(Tile::Pane(Pane::DatabaseEntries(entries))) =
+                    self.tree.tiles.get_mut(tile_id)
+                {
+                    entries.alias = Some(alias).filter(|alias| !alias.trim().is_empty());
+                }
+            }
+            if let Some((tile_id, pane)) = pane_to_open {
+                self.open_sibling_pane(tile_id, pane);
+            }
+            if let Some(tile_id) = tile_to_split {
+                self.split_right(tile_id);
+            }
+
+            // Automatically insert an OpenNew Tab when one is missing
+            if let Some(root) = self.tree.root() {
+                let must_insert = match self.tree.tiles.get(root).unwrap() {
+                    Tile::Container(Container::Tabs(tabs)) => {
+                        !tabs.children.iter().any(|&tile_id| {
+                            self.tree.tiles.get(tile_id).is_none_or(
+                                |tile| matches!(tile, Tile::Pane(pane) if pane.is_open_new()),
+                            )
+                        })
+                    }
+                    _ => false,
+                };
+
+                if must_insert {
+                    let tid = self
+                        .tree
+                        .tiles
+                        .insert_pane(Pane::OpenNew { database_to_open: String::new() });
+                    if let Tile::Container(Container::Tabs(t)) =
+                        self.tree.tiles.get_mut(root).unwrap()
+                    {
+                        t.children.push(tid);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// The environment information displayed in the status bar.
+#[derive(Debug, Clone, Copy)]
+struct EnvStatus {
+    last_txn_id: usize,
+    /// The number of bytes of the map used by the pages.
+    map_used: usize,
+    map_size: usize,
+    readers: u32,
+}
+
+impl EnvStatus {
+    fn read(env: &Env, rtxn: &RoTxn) -> EnvStatus {
+        let info = env.info();
+        // The page size is only exposed by the database statistics.
+        let page_size = match env.open_database::<Bytes, Bytes>(rtxn, None) {
+            Ok(Some(main_db)) => main_db.stat(rtxn).map_or(0, |stat| stat.page_size as usize),
+            _ => 0,
+        };
+        EnvStatus {
+            last_txn_id: info.last_txn_id,
+            map_used: (info.last_page_number + 1) * page_size,
+            map_size: info.map_size,
+            readers: info.number_of_readers,
+        }
+    }
+
+    /// The used fraction of the map, between 0 and 1.
+    fn fill_ratio(&self) -> f64 {
+        self.map_used as f64 / self.map_size as f64
+    }
+}
+
+/// Opens the named database in the given transaction and returns a pane displaying it.
+pub fn database_entries_pane(
+    env: &Env,
+    rtxn: &RoTxn,
+    database_name: String,
+) -> heed::Result<Option<Pane>> {
+    let database = env.open_database(rtxn, Some(&database_name))?;
+    Ok(database.map(|database| Pane::database_entries(env, rtxn, Some(database_name), database)))
+}
+
+/// Displays the entry count or the error of every checked database.
+fn integrity_report_ui(ui: &mut egui::Ui, report: &IntegrityReport) {
+    match report.errors() {
+        0 => ui.colored_label(
+            Color32::GREEN,
+            format!("The {} databases are consistent", report.databases.len()),
+        ),
+        errors => ui.colored_label(Color32::RED, format!("{errors} databases have errors")),
+    };
+    if report.plain_keys != 0 {
+        ui.label(format!(
+            "{} keys of the main database are entries and not databases",
+            report.plain_keys
+        ));
+    }
+    ui.separator();
+
+    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+        egui::Grid::new("integrity_report").num_columns(3).striped(true).show(ui, |ui| {
+            for check in &report.databases {
+                ui.label(check.name.as_deref().unwrap_or("{main}"));
+                ui.label(format!("{} entries", check.entries));
+                match &check.error {
+                    Some(error) => ui.colored_label(Color32::RED, error.as_str()),
+                    None => ui.colored_label(Color32::GREEN, "ok"),
+                };
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// Returns the number of entries of the unnamed database, zero when it does not exist.
+fn main_db_len(env: &Env, rtxn: &RoTxn) -> heed::Result<u64> {
+    match env.open_database::<Str, DecodeIgnore>(rtxn, None)? {
+        Some(main_db) => main_db.len(rtxn),
+        None => Ok(0),
+    }
+}
+
+/// Lists the named databases by iterating over the keys of the unnamed one.
+///
+/// The unnamed database can also contain regular entries,
+/// we only keep the keys that can be opened as a database.
+fn list_database_names(env: &Env, rtxn: &RoTxn) -> heed::Result<Vec<String>> {
+    let Some(main_db) = env.open_database::<Str, DecodeIgnore>(rtxn, None)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut names = Vec::new();
+    for result in main_db.iter(rtxn)? {
+        // Named databases are always valid UTF-8 keys.
+        let Ok((name, ())) = result else { continue };
+        if let Ok(Some(_)) = env.open_database::<Bytes, Bytes>(rtxn, Some(name)) {
+            names.push(name.to_owned());
+        }
+    }
+    Ok(names)
+}